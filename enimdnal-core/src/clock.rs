@@ -0,0 +1,214 @@
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+/// Source of monotonic time for a [GameClock].
+pub trait Clock {
+    fn now(&self) -> Instant;
+}
+
+/// The real time, as told by the operating system's monotonic clock.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+/// Time that only moves when told to, for testing code built around a [GameClock].
+///
+/// Clones share the same time, so a clone kept aside can advance the clock
+/// handed over to the code under test.
+#[derive(Debug, Clone)]
+pub struct FakeClock {
+    start: Instant,
+    elapsed: Rc<Cell<Duration>>,
+}
+
+/// Stopwatch timing a run, which can be paused and resumed any number of times.
+///
+/// Time is measured between monotonic instants rather than summed up frame by frame,
+/// so that it neither drifts nor depends on the frame rate.
+#[derive(Debug, Clone)]
+pub struct GameClock<C: Clock = SystemClock> {
+    clock: C,
+
+    /// Time counted up to the last pause.
+    banked: Duration,
+
+    /// Start of the current stretch of running, unless paused.
+    running_since: Option<Instant>,
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+impl FakeClock {
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            elapsed: Rc::new(Cell::new(Duration::ZERO)),
+        }
+    }
+
+    pub fn advance(&self, by: Duration) {
+        self.elapsed.set(self.elapsed.get() + by);
+    }
+}
+
+impl Default for FakeClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for FakeClock {
+    fn now(&self) -> Instant {
+        self.start + self.elapsed.get()
+    }
+}
+
+impl GameClock {
+    /// A paused clock at zero, running on the system time.
+    pub fn new() -> Self {
+        Self::with_clock(SystemClock)
+    }
+}
+
+impl Default for GameClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C: Clock> GameClock<C> {
+    /// A paused clock at zero, running on the given time source.
+    pub fn with_clock(clock: C) -> Self {
+        Self {
+            clock,
+            banked: Duration::ZERO,
+            running_since: None,
+        }
+    }
+
+    /// Start counting, doing nothing if the clock already runs.
+    pub fn start(&mut self) {
+        if self.running_since.is_none() {
+            self.running_since = Some(self.clock.now());
+        }
+    }
+
+    /// Stop counting, keeping the time so far.
+    pub fn pause(&mut self) {
+        if let Some(since) = self.running_since.take() {
+            self.banked += self.clock.now().saturating_duration_since(since);
+        }
+    }
+
+    /// Go back to zero, paused.
+    pub fn reset(&mut self) {
+        self.banked = Duration::ZERO;
+        self.running_since = None;
+    }
+
+    /// Go back to `elapsed`, paused, e.g. to continue a saved game.
+    pub fn set_elapsed(&mut self, elapsed: Duration) {
+        self.banked = elapsed;
+        self.running_since = None;
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running_since.is_some()
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        let running = self.running_since.map_or(Duration::ZERO, |since| {
+            self.clock.now().saturating_duration_since(since)
+        });
+
+        self.banked + running
+    }
+
+    /// Elapsed time in whole milliseconds, as stored in records and replays.
+    ///
+    /// Saturates after about 49 days.
+    pub fn elapsed_milisec(&self) -> u32 {
+        self.elapsed().as_millis().try_into().unwrap_or(u32::MAX)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clock() -> (GameClock<FakeClock>, FakeClock) {
+        let time = FakeClock::new();
+        (GameClock::with_clock(time.clone()), time)
+    }
+
+    #[test]
+    fn counts_only_while_running() {
+        let (mut clock, time) = clock();
+
+        time.advance(Duration::from_millis(500));
+        assert_eq!(clock.elapsed_milisec(), 0);
+
+        clock.start();
+        time.advance(Duration::from_millis(1_250));
+        assert!(clock.is_running());
+        assert_eq!(clock.elapsed_milisec(), 1_250);
+    }
+
+    #[test]
+    fn pausing_keeps_the_time_so_far() {
+        let (mut clock, time) = clock();
+
+        clock.start();
+        time.advance(Duration::from_millis(300));
+        clock.pause();
+        time.advance(Duration::from_millis(10_000));
+        assert!(!clock.is_running());
+        assert_eq!(clock.elapsed_milisec(), 300);
+
+        clock.start();
+        time.advance(Duration::from_millis(200));
+        assert_eq!(clock.elapsed_milisec(), 500);
+    }
+
+    #[test]
+    fn starting_twice_does_not_restart() {
+        let (mut clock, time) = clock();
+
+        clock.start();
+        time.advance(Duration::from_millis(400));
+        clock.start();
+        time.advance(Duration::from_millis(100));
+        assert_eq!(clock.elapsed_milisec(), 500);
+    }
+
+    #[test]
+    fn set_elapsed_continues_from_the_given_time() {
+        let (mut clock, time) = clock();
+
+        clock.start();
+        time.advance(Duration::from_millis(700));
+        clock.set_elapsed(Duration::from_millis(42_000));
+        assert!(!clock.is_running());
+        assert_eq!(clock.elapsed_milisec(), 42_000);
+
+        clock.start();
+        time.advance(Duration::from_millis(1));
+        assert_eq!(clock.elapsed_milisec(), 42_001);
+    }
+
+    #[test]
+    fn reset_goes_back_to_zero() {
+        let (mut clock, time) = clock();
+
+        clock.start();
+        time.advance(Duration::from_millis(900));
+        clock.reset();
+        time.advance(Duration::from_millis(900));
+        assert!(!clock.is_running());
+        assert_eq!(clock.elapsed_milisec(), 0);
+    }
+}
@@ -0,0 +1,15 @@
+//! Minesweeper game logic, free of any graphics or windowing.
+//!
+//! The [minefield] module holds the board itself along with a deductive solver
+//! and game statistics, [random] the seeds boards are generated from,
+//! [replay] the recording and playback of whole games,
+//! and [clock] the timing of runs.
+
+pub mod clock;
+pub mod minefield;
+pub mod random;
+pub mod replay;
+
+pub use clock::GameClock;
+pub use minefield::{Board, Params, ParamsError};
+pub use random::Seed;
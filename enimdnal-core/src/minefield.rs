@@ -0,0 +1,1348 @@
+pub mod solver;
+pub mod stats;
+pub mod topology;
+
+use std::collections::HashSet;
+use std::fmt;
+use std::iter;
+
+use nanorand::WyRand;
+use serde::{Deserialize, Serialize};
+
+use crate::random::{IteratorRandom, Seed};
+
+use topology::{Adjacency, MAX_REACH};
+
+pub use topology::{Neighborhood, Offsets, Topology};
+
+/// How many layouts the [Generation::NoGuess] mode tries
+/// before settling for one that needs guessing.
+const NO_GUESS_ATTEMPTS: usize = 10_000;
+
+/// Solver steps the [Generation::NoGuess] mode may spend on all its attempts together,
+/// so that boards too dense to ever be solvable do not keep the player waiting.
+///
+/// Counted in steps rather than time, for the layout to still only depend on the seed.
+const NO_GUESS_EFFORT: usize = 5_000_000;
+
+/// Largest supported board width and height.
+pub const MAX_DIMENSION: usize = 200;
+
+/// Most mines a single tile can hold, with [Distribution::Stacked].
+pub const MAX_STACK: u8 = 3;
+
+pub const BEGINNER: Params = Params {
+    width: 8,
+    height: 8,
+    mines: 10,
+    generation: Generation::Random,
+    topology: Topology::Square,
+    wrapping: false,
+    neighborhood: Neighborhood::Adjacent,
+    distribution: Distribution::Single,
+};
+pub const INTERMEDIATE: Params = Params {
+    width: 16,
+    height: 16,
+    mines: 40,
+    generation: Generation::Random,
+    topology: Topology::Square,
+    wrapping: false,
+    neighborhood: Neighborhood::Adjacent,
+    distribution: Distribution::Single,
+};
+pub const EXPERT: Params = Params {
+    width: 30,
+    height: 16,
+    mines: 99,
+    generation: Generation::Random,
+    topology: Topology::Square,
+    wrapping: false,
+    neighborhood: Neighborhood::Adjacent,
+    distribution: Distribution::Single,
+};
+
+/// Dimensions and mine count of a board, validated by [Params::new].
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+#[serde(try_from = "UncheckedParams")]
+pub struct Params {
+    width: usize,
+    height: usize,
+    mines: usize,
+    generation: Generation,
+    topology: Topology,
+
+    /// Whether the board wraps around its edges like a torus,
+    /// so that tiles on opposite edges neighbor each other.
+    wrapping: bool,
+    neighborhood: Neighborhood,
+    distribution: Distribution,
+}
+
+/// Why a board with the requested [Params] cannot be played.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ParamsError {
+    ZeroDimensions,
+
+    /// Not enough tiles are left for the mines once the safe zone
+    /// around the first uncovered tile is cleared.
+    TooManyMines {
+        max: usize,
+    },
+
+    /// Width or height exceed [MAX_DIMENSION].
+    TooLarge,
+
+    /// Wrapping boards need more rows and columns than the neighbors reach across,
+    /// so that no tile neighbors itself or another tile twice,
+    /// and hex ones an even number of rows, for the rows to keep alternating across the seam.
+    Unwrappable,
+
+    /// A [Neighborhood] with no neighbors at all, or ones out of reach.
+    BadNeighborhood,
+
+    /// A [Distribution::Stacked] of fewer than 2 or more than [MAX_STACK] mines.
+    BadStack,
+}
+
+/// Deserialization counterpart of [Params], checked before use.
+#[derive(Deserialize)]
+struct UncheckedParams {
+    width: usize,
+    height: usize,
+    mines: usize,
+    generation: Generation,
+    #[serde(default)]
+    topology: Topology,
+    #[serde(default)]
+    wrapping: bool,
+    #[serde(default)]
+    neighborhood: Neighborhood,
+    #[serde(default)]
+    distribution: Distribution,
+}
+
+impl Params {
+    pub fn new(width: usize, height: usize, mines: usize) -> Result<Self, ParamsError> {
+        Self {
+            width,
+            height,
+            mines,
+            generation: Generation::Random,
+            topology: Topology::Square,
+            wrapping: false,
+            neighborhood: Neighborhood::Adjacent,
+            distribution: Distribution::Single,
+        }
+        .check()
+    }
+
+    pub fn with_mines(self, mines: usize) -> Result<Self, ParamsError> {
+        Self { mines, ..self }.check()
+    }
+
+    pub fn with_generation(self, generation: Generation) -> Self {
+        Self { generation, ..self }
+    }
+
+    pub fn with_topology(self, topology: Topology) -> Result<Self, ParamsError> {
+        Self { topology, ..self }.check()
+    }
+
+    pub fn with_wrapping(self, wrapping: bool) -> Result<Self, ParamsError> {
+        Self { wrapping, ..self }.check()
+    }
+
+    pub fn with_neighborhood(self, neighborhood: Neighborhood) -> Result<Self, ParamsError> {
+        Self {
+            neighborhood,
+            ..self
+        }
+        .check()
+    }
+
+    pub fn with_distribution(self, distribution: Distribution) -> Result<Self, ParamsError> {
+        Self {
+            distribution,
+            ..self
+        }
+        .check()
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn mines(&self) -> usize {
+        self.mines
+    }
+
+    pub fn generation(&self) -> Generation {
+        self.generation
+    }
+
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    pub fn wrapping(&self) -> bool {
+        self.wrapping
+    }
+
+    pub fn neighborhood(&self) -> Neighborhood {
+        self.neighborhood
+    }
+
+    pub fn distribution(&self) -> Distribution {
+        self.distribution
+    }
+
+    /// Make sure that a board can be played with these params.
+    fn check(self) -> Result<Self, ParamsError> {
+        let (width, height) = (self.width, self.height);
+
+        if width == 0 || height == 0 {
+            return Err(ParamsError::ZeroDimensions);
+        }
+
+        if width > MAX_DIMENSION || height > MAX_DIMENSION {
+            return Err(ParamsError::TooLarge);
+        }
+
+        if !self.neighborhood.is_valid() {
+            return Err(ParamsError::BadNeighborhood);
+        }
+
+        if let Distribution::Stacked(stack) = self.distribution {
+            if !(2..=MAX_STACK).contains(&stack) {
+                return Err(ParamsError::BadStack);
+            }
+        }
+
+        let adjacency = Adjacency::new(&self);
+
+        if self.wrapping {
+            let (reach_x, reach_y) = adjacency.reach();
+            let too_small = width < reach_x * 2 + 1 || height < reach_y * 2 + 1;
+            let odd_hex_rows = self.topology == Topology::Hex && !height.is_multiple_of(2);
+            if too_small || odd_hex_rows {
+                return Err(ParamsError::Unwrappable);
+            }
+        }
+
+        // the first uncovered tile and its neighbors never hold a mine,
+        // so the placement has to fit even with the largest safe zone
+        let stack = self.distribution.stack() as usize;
+        let max = (width * height - adjacency.largest_zone()) * stack;
+        if self.mines > max {
+            return Err(ParamsError::TooManyMines { max });
+        }
+
+        Ok(self)
+    }
+}
+
+impl TryFrom<UncheckedParams> for Params {
+    type Error = ParamsError;
+
+    fn try_from(value: UncheckedParams) -> Result<Self, Self::Error> {
+        Self {
+            width: value.width,
+            height: value.height,
+            mines: value.mines,
+            generation: value.generation,
+            topology: value.topology,
+            wrapping: value.wrapping,
+            neighborhood: value.neighborhood,
+            distribution: value.distribution,
+        }
+        .check()
+    }
+}
+
+impl TryFrom<UncheckedBoard> for Board {
+    type Error = String;
+
+    fn try_from(value: UncheckedBoard) -> Result<Self, Self::Error> {
+        let size = value.params.width * value.params.height;
+        if value.tiles.len() != size {
+            return Err(format!(
+                "expected {} tiles, found {}",
+                size,
+                value.tiles.len()
+            ));
+        }
+
+        let (covered, flags) = count_covers(&value.tiles);
+        if value.covered != covered || value.flags != flags {
+            return Err("tile counters do not match the tiles".to_string());
+        }
+
+        let mine_tiles = count_mine_tiles(&value.tiles);
+
+        Ok(Self {
+            tiles: value.tiles,
+            covered: value.covered,
+            flags: value.flags,
+            mine_tiles,
+            params: value.params,
+            seed: value.seed,
+            placed: value.placed,
+            fallback: value.fallback,
+            defeat: value.defeat,
+            history: History::default(),
+            clicks: value.clicks,
+            mark_cycle: value.mark_cycle,
+            adjacency: Adjacency::new(&value.params),
+        })
+    }
+}
+
+impl fmt::Display for ParamsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ZeroDimensions => write!(f, "Board must be at least 1x1"),
+            Self::TooManyMines { max } => write!(f, "At most {max} mines fit on this board"),
+            Self::TooLarge => write!(
+                f,
+                "Board can be at most {MAX_DIMENSION}x{MAX_DIMENSION} tiles"
+            ),
+            Self::Unwrappable => write!(
+                f,
+                "Board is too small to wrap around, or has an odd height with hex tiles"
+            ),
+            Self::BadNeighborhood => write!(f, "Neighbors must be 1 to {MAX_REACH} tiles away"),
+            Self::BadStack => write!(f, "Tiles can hold 2 to {MAX_STACK} mines when stacked"),
+        }
+    }
+}
+
+impl std::error::Error for ParamsError {}
+
+/// Strategy of laying out the mines once the first tile is uncovered.
+///
+/// Either way, the first uncovered tile and its neighbors never hold a mine.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default, Serialize, Deserialize)]
+pub enum Generation {
+    /// Mines are scattered uniformly at random.
+    #[default]
+    Random,
+
+    /// Layouts are rerolled until the whole board
+    /// can be cleared by logic alone, without a single guess.
+    NoGuess,
+}
+
+/// How the mines are spread over the tiles.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default, Serialize, Deserialize)]
+pub enum Distribution {
+    /// At most one mine per tile, as in classic minesweeper.
+    #[default]
+    Single,
+
+    /// Up to the given number of mines per tile, from 2 to [MAX_STACK].
+    ///
+    /// Every tile is thought of as that many slots, and the mines pick slots at random,
+    /// so a tile is more likely to hold a single mine than a whole stack.
+    Stacked(u8),
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub enum Mark {
+    /// Mine flag, indicates 100% player certainty of the given number of mines,
+    /// and disables uncovering the marked field, for safety.
+    ///
+    /// Numbers above one are only offered on boards with [Distribution::Stacked] mines.
+    Flag(u8),
+
+    /// "Danger, probably" marker, for fields that are sorta suspicious,
+    /// but not yet worthy of The [Mark::Flag].
+    Unsure,
+    None,
+}
+
+/// Order in which the secondary action cycles through the [Mark]s.
+///
+/// On boards with stacked mines, the flag steps through every number of mines
+/// a tile can hold before moving on.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default, Serialize, Deserialize)]
+pub enum MarkCycle {
+    /// None → Flag → None, for players who never need the unsure mark.
+    FlagOnly,
+
+    /// None → Flag → Unsure → None.
+    #[default]
+    FlagUnsure,
+
+    /// None → Unsure → Flag → None, for marking suspicions before committing to them.
+    UnsureFirst,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub enum Cover {
+    Up(Mark),
+    Down,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub enum Object {
+    /// One or more mines, more only with [Distribution::Stacked].
+    Mine(u8),
+
+    /// Number of mines on the neighboring tiles, counting every mine of a stack.
+    Hint(u8),
+    Blank,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub struct Tile {
+    cover: Cover,
+    object: Object,
+}
+
+/// Saved boards leave out the undo history.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "UncheckedBoard")]
+pub struct Board {
+    tiles: Vec<Tile>,
+    covered: usize,
+
+    /// Mines flagged, which is more than the flagged tiles with stacked flags.
+    flags: usize,
+
+    /// Tiles holding at least one mine, which are the ones left covered on victory.
+    #[serde(skip)]
+    mine_tiles: usize,
+    params: Params,
+    seed: Seed,
+    placed: bool,
+
+    /// Set when [Generation::NoGuess] gave up on finding a layout
+    /// that can be cleared without guessing.
+    fallback: bool,
+    defeat: bool,
+    #[serde(skip)]
+    history: History,
+    clicks: Clicks,
+    mark_cycle: MarkCycle,
+    #[serde(skip)]
+    adjacency: Adjacency,
+}
+
+/// Deserialization counterpart of [Board], checked before use.
+#[derive(Deserialize)]
+struct UncheckedBoard {
+    tiles: Vec<Tile>,
+    covered: usize,
+    flags: usize,
+    params: Params,
+    seed: Seed,
+    placed: bool,
+    #[serde(default)]
+    fallback: bool,
+    defeat: bool,
+    clicks: Clicks,
+    #[serde(default)]
+    mark_cycle: MarkCycle,
+}
+
+/// Clicks made on the board so far, whether or not they changed anything.
+///
+/// Undoing an action does not take its click back.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub struct Clicks {
+    /// Primary actions on covered tiles.
+    pub left: u32,
+    pub right: u32,
+
+    /// Primary actions on uncovered hint tiles.
+    pub chord: u32,
+}
+
+impl Clicks {
+    pub fn total(&self) -> u32 {
+        self.left + self.right + self.chord
+    }
+}
+
+/// Board state kept outside of the tiles themselves.
+#[derive(Debug, Clone, Copy)]
+struct Counters {
+    covered: usize,
+    flags: usize,
+    mine_tiles: usize,
+    placed: bool,
+    fallback: bool,
+    defeat: bool,
+}
+
+/// A single player action, as the set of tiles it has changed.
+///
+/// Cascading effects, like flood fills or exploring around a hint,
+/// belong to the action that triggered them.
+#[derive(Debug, Clone)]
+struct Step {
+    /// Board index, tile before and after the action.
+    changes: Vec<(usize, Tile, Tile)>,
+    before: Counters,
+    after: Counters,
+}
+
+#[derive(Debug, Clone, Default)]
+struct History {
+    undo: Vec<Step>,
+    redo: Vec<Step>,
+}
+
+impl Distribution {
+    /// Most mines a single tile can hold.
+    pub fn stack(self) -> u8 {
+        match self {
+            Distribution::Single => 1,
+            Distribution::Stacked(stack) => stack,
+        }
+    }
+}
+
+impl Mark {
+    /// Move on to the next mark, with flags of up to `stack` mines.
+    fn cycle(&mut self, order: MarkCycle, stack: u8) {
+        *self = match (order, *self) {
+            (_, Self::Flag(flags)) if flags < stack => Self::Flag(flags + 1),
+            (MarkCycle::FlagOnly, Self::None) => Self::Flag(1),
+            (MarkCycle::FlagOnly, _) => Self::None,
+            (MarkCycle::FlagUnsure, Self::None) => Self::Flag(1),
+            (MarkCycle::FlagUnsure, Self::Flag(_)) => Self::Unsure,
+            (MarkCycle::FlagUnsure, Self::Unsure) => Self::None,
+            (MarkCycle::UnsureFirst, Self::None) => Self::Unsure,
+            (MarkCycle::UnsureFirst, Self::Unsure) => Self::Flag(1),
+            (MarkCycle::UnsureFirst, Self::Flag(_)) => Self::None,
+        };
+    }
+}
+
+impl Tile {
+    fn new() -> Self {
+        Self {
+            cover: Cover::Up(Mark::None),
+            object: Object::Blank,
+        }
+    }
+
+    pub fn cover(&self) -> Cover {
+        self.cover
+    }
+
+    pub fn object(&self) -> Object {
+        self.object
+    }
+
+    pub fn is_uncoverable(&self) -> bool {
+        matches!(self.cover, Cover::Up(Mark::None | Mark::Unsure))
+    }
+
+    pub fn is_mine(&self) -> bool {
+        matches!(self.object, Object::Mine(_))
+    }
+
+    /// Number of mines the tile holds.
+    pub fn mines(&self) -> u8 {
+        match self.object {
+            Object::Mine(mines) => mines,
+            _ => 0,
+        }
+    }
+
+    /// Number of mines the player has flagged on the tile.
+    pub fn flags(&self) -> u8 {
+        match self.cover {
+            Cover::Up(Mark::Flag(flags)) => flags,
+            _ => 0,
+        }
+    }
+
+    pub fn is_hint(&self) -> bool {
+        matches!(self.object, Object::Hint(_))
+    }
+
+    pub fn is_blank(&self) -> bool {
+        matches!(self.object, Object::Blank)
+    }
+
+    pub fn is_flag(&self) -> bool {
+        matches!(self.cover, Cover::Up(Mark::Flag(_)))
+    }
+}
+
+impl Board {
+    pub fn new(params: Params) -> Self {
+        Self::with_seed(params, Seed::random())
+    }
+
+    /// Create a board whose mine layout is fully determined
+    /// by the `seed` and the coordinates of the first uncovered tile.
+    pub fn with_seed(params: Params, seed: Seed) -> Self {
+        let size = params.width * params.height;
+        Self {
+            tiles: vec![Tile::new(); size],
+            covered: size,
+            flags: 0,
+            mine_tiles: 0,
+            placed: false,
+            fallback: false,
+            defeat: false,
+            params,
+            seed,
+            history: History::default(),
+            clicks: Clicks::default(),
+            mark_cycle: MarkCycle::default(),
+            adjacency: Adjacency::new(&params),
+        }
+    }
+
+    pub fn beginner() -> Self {
+        Self::new(BEGINNER)
+    }
+
+    pub fn intermediate() -> Self {
+        Self::new(INTERMEDIATE)
+    }
+
+    pub fn expert() -> Self {
+        Self::new(EXPERT)
+    }
+
+    pub fn params(&self) -> Params {
+        self.params
+    }
+
+    pub fn dims(&self) -> (usize, usize) {
+        (self.params.width, self.params.height)
+    }
+
+    pub fn mines(&self) -> usize {
+        self.params.mines
+    }
+
+    pub fn seed(&self) -> Seed {
+        self.seed
+    }
+
+    pub fn tile(&self, x: usize, y: usize) -> Tile {
+        let index = self.coords_to_index(x, y);
+        self.tiles[index]
+    }
+
+    /// Number of mines flagged, counting every mine of a stacked flag.
+    pub fn flags(&self) -> usize {
+        self.flags
+    }
+
+    /// Mines not accounted for by flags, negative when there are more flags than mines.
+    ///
+    /// Flags are not checked against the mines, so this only tells how many are left
+    /// if the player has not made any mistakes.
+    pub fn mines_remaining(&self) -> isize {
+        self.params.mines as isize - self.flags as isize
+    }
+
+    /// Whether only the tiles holding mines are left covered,
+    /// of which there are fewer than mines when they are stacked.
+    pub fn is_victory(&self) -> bool {
+        self.covered == self.mine_tiles
+    }
+
+    pub fn is_defeat(&self) -> bool {
+        self.defeat
+    }
+
+    pub fn clicks(&self) -> Clicks {
+        self.clicks
+    }
+
+    pub fn is_initialized(&self) -> bool {
+        self.placed
+    }
+
+    /// Whether the board was meant to be cleared without guessing,
+    /// but no such layout was found in time and it may need guessing after all.
+    pub fn is_no_guess_fallback(&self) -> bool {
+        self.fallback
+    }
+
+    pub fn mark_cycle(&self) -> MarkCycle {
+        self.mark_cycle
+    }
+
+    /// Change the order of marks for the secondary actions to come.
+    ///
+    /// Tiles that are already marked keep their marks.
+    pub fn set_mark_cycle(&mut self, order: MarkCycle) {
+        self.mark_cycle = order;
+    }
+
+    /// Primary interface for acting on a minefield.
+    ///
+    /// Corresponds to one of the primary actions on a tile:
+    ///
+    /// - uncovering a covered tile
+    /// - doing an "explore" action around a hint tile
+    ///
+    /// Uncovering every non-mine tile is the win condition.
+    /// Note that the mine tiles are **not** required to be flagged (looking at you, speedrunners).
+    pub fn handle_primary_action(&mut self, x: usize, y: usize) {
+        match self.tile(x, y) {
+            Tile {
+                cover: Cover::Down,
+                object: Object::Hint(_),
+            } => self.clicks.chord += 1,
+            _ => self.clicks.left += 1,
+        }
+
+        self.record(|board| board.primary_action(x, y));
+    }
+
+    /// Explore around an uncovered hint tile, without the risk
+    /// of uncovering the tile itself when aimed at a covered one by mistake.
+    pub fn handle_chord_action(&mut self, x: usize, y: usize) {
+        self.clicks.chord += 1;
+
+        if let Tile {
+            cover: Cover::Down,
+            object: Object::Hint(hint),
+        } = self.tile(x, y)
+        {
+            self.record(|board| board.explore_around(hint, x, y));
+        }
+    }
+
+    /// Primary interface for acting on a minefield.
+    ///
+    /// Corresponds to the action of cycling through
+    /// available covered-tile marks (the [Mark] type), in the [Board::mark_cycle] order.
+    pub fn handle_secondary_action(&mut self, x: usize, y: usize) {
+        self.clicks.right += 1;
+        self.record(|board| board.secondary_action(x, y));
+    }
+
+    /// Revert the most recent action, including a fatal one.
+    ///
+    /// Returns whether there was anything to revert.
+    pub fn undo(&mut self) -> bool {
+        let Some(step) = self.history.undo.pop() else {
+            return false;
+        };
+
+        for &(idx, before, _) in &step.changes {
+            self.tiles[idx] = before;
+        }
+        self.set_counters(step.before);
+        self.history.redo.push(step);
+        self.check_counters();
+
+        true
+    }
+
+    /// Repeat the most recently undone action.
+    ///
+    /// Returns whether there was anything to repeat.
+    pub fn redo(&mut self) -> bool {
+        let Some(step) = self.history.redo.pop() else {
+            return false;
+        };
+
+        for &(idx, _, after) in &step.changes {
+            self.tiles[idx] = after;
+        }
+        self.set_counters(step.after);
+        self.history.undo.push(step);
+        self.check_counters();
+
+        true
+    }
+
+    fn primary_action(&mut self, x: usize, y: usize) {
+        if !self.placed {
+            self.place_mines_and_hints(x, y);
+            self.placed = true;
+        }
+
+        let tile_idx = self.coords_to_index(x, y);
+        let tile = &mut self.tiles[tile_idx];
+
+        if let (Cover::Down, Object::Hint(hint)) = (tile.cover, tile.object) {
+            self.explore_around(hint, x, y);
+            return;
+        }
+
+        if !tile.is_uncoverable() {
+            return;
+        }
+
+        self.uncover(x, y);
+    }
+
+    fn secondary_action(&mut self, x: usize, y: usize) {
+        let tile_idx = self.coords_to_index(x, y);
+        let Cover::Up(mut mark) = self.tiles[tile_idx].cover else {
+            return;
+        };
+
+        mark.cycle(self.mark_cycle, self.params.distribution.stack());
+        self.set_cover(tile_idx, Cover::Up(mark));
+    }
+
+    /// Start a new game on a board generated from `seed`.
+    ///
+    /// Passing the current [Board::seed] restarts the same board.
+    pub fn reset(&mut self, seed: Seed) {
+        self.tiles.fill(Tile::new());
+        self.seed = seed;
+        self.placed = false;
+        self.fallback = false;
+        self.defeat = false;
+        self.covered = self.tiles.len();
+        self.flags = 0;
+        self.mine_tiles = 0;
+        self.history = History::default();
+        self.clicks = Clicks::default();
+        self.check_counters();
+    }
+
+    /// Run an action, remembering its effects as a single undoable step.
+    ///
+    /// Actions that change nothing are not remembered.
+    fn record(&mut self, action: impl FnOnce(&mut Self)) {
+        let tiles_before = self.tiles.clone();
+        let before = self.counters();
+
+        action(self);
+        self.check_counters();
+
+        let changes: Vec<_> = tiles_before
+            .into_iter()
+            .zip(&self.tiles)
+            .enumerate()
+            .filter(|(_, (old, new))| old != *new)
+            .map(|(idx, (old, &new))| (idx, old, new))
+            .collect();
+
+        if changes.is_empty() {
+            return;
+        }
+
+        let after = self.counters();
+        self.history.undo.push(Step {
+            changes,
+            before,
+            after,
+        });
+        self.history.redo.clear();
+    }
+
+    fn counters(&self) -> Counters {
+        Counters {
+            covered: self.covered,
+            flags: self.flags,
+            mine_tiles: self.mine_tiles,
+            placed: self.placed,
+            fallback: self.fallback,
+            defeat: self.defeat,
+        }
+    }
+
+    fn set_counters(&mut self, counters: Counters) {
+        self.covered = counters.covered;
+        self.flags = counters.flags;
+        self.mine_tiles = counters.mine_tiles;
+        self.placed = counters.placed;
+        self.fallback = counters.fallback;
+        self.defeat = counters.defeat;
+    }
+
+    /// Change the cover of a tile, along with the counters of covered and flagged tiles.
+    ///
+    /// Actions change covers only through here, so that the counters cannot drift.
+    fn set_cover(&mut self, idx: usize, cover: Cover) {
+        let old = std::mem::replace(&mut self.tiles[idx].cover, cover);
+        let covered = |cover: Cover| usize::from(cover != Cover::Down);
+        let flagged = |cover: Cover| match cover {
+            Cover::Up(Mark::Flag(flags)) => flags as usize,
+            _ => 0,
+        };
+
+        self.covered = self.covered + covered(cover) - covered(old);
+        self.flags = self.flags + flagged(cover) - flagged(old);
+    }
+
+    /// Make sure that the counters agree with the tiles, in debug builds only.
+    fn check_counters(&self) {
+        if cfg!(debug_assertions) {
+            let (covered, flags) = count_covers(&self.tiles);
+            debug_assert_eq!(self.covered, covered, "covered tiles miscounted");
+            debug_assert_eq!(self.flags, flags, "flags miscounted");
+            debug_assert_eq!(
+                self.mine_tiles,
+                count_mine_tiles(&self.tiles),
+                "mine tiles miscounted"
+            );
+        }
+    }
+
+    /// Number of steps between two tiles, going from neighbor to neighbor
+    /// of the [Topology], and across the edges on wrapping boards.
+    pub fn distance(&self, from: (usize, usize), to: (usize, usize)) -> usize {
+        let wrap = self.params.wrapping.then_some(self.dims());
+        self.params.topology.steps(from, to, wrap)
+    }
+
+    /// Tiles whose mines the hint at `x`, `y` counts,
+    /// following the [Topology], [Neighborhood] and wrapping of the board.
+    pub fn neighbors(&self, x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.adjacency.neighbors(x, y)
+    }
+
+    /// A flood-fill-style uncovering procedure,
+    /// where the uncovering "spills" over a surrounding area
+    /// bounded by hint tiles (inclusive).
+    ///
+    /// Algorithmically, this is equivalent to a DFS/BFS traversal
+    /// starting from a player-uncovered tile
+    /// and stopping on already uncovered tiles and hint tiles.
+    fn flood_uncover(&mut self, x: usize, y: usize) {
+        let mut flooded: Vec<_> = self.neighbors(x, y).collect();
+        let mut visited = HashSet::new();
+
+        while let Some((current_x, current_y)) = flooded.pop() {
+            let t_idx = self.coords_to_index(current_x, current_y);
+            let tile = self.tiles[t_idx];
+
+            if !tile.is_uncoverable() || !visited.insert((current_x, current_y)) {
+                continue;
+            }
+
+            self.set_cover(t_idx, Cover::Down);
+
+            if tile.is_blank() {
+                let n = self.neighbors(current_x, current_y);
+                flooded.extend(n);
+            }
+        }
+    }
+
+    fn uncover(&mut self, x: usize, y: usize) {
+        let tile_idx = self.coords_to_index(x, y);
+        self.set_cover(tile_idx, Cover::Down);
+
+        match self.tiles[tile_idx].object {
+            Object::Mine(_) => self.defeat = true,
+            Object::Blank => self.flood_uncover(x, y),
+            Object::Hint(_) => (),
+        }
+    }
+
+    /// Clicking on a hint tile if there are exactly as many flagged mines around it as hinted
+    /// causes the remaining covered tiles to be uncovered automatically.
+    ///
+    /// Beware: if the flags are misplaced, this is an instant defeat!
+    fn explore_around(&mut self, hinted: u8, x: usize, y: usize) {
+        let neighbors: Vec<_> = self.neighbors(x, y).collect();
+        let n_flags: usize = neighbors
+            .iter()
+            .map(|&(xx, yy)| self.tiles[self.coords_to_index(xx, yy)].flags() as usize)
+            .sum();
+
+        if hinted as usize != n_flags {
+            return;
+        }
+
+        let explored: Vec<_> = neighbors
+            .iter()
+            .copied()
+            .filter(|&(xx, yy)| self.tiles[self.coords_to_index(xx, yy)].is_uncoverable())
+            .collect();
+
+        for (current_x, current_y) in explored {
+            self.uncover(current_x, current_y);
+        }
+    }
+
+    /// Get single dimension index of 2D tile in tiles array
+    fn coords_to_index(&self, x: usize, y: usize) -> usize {
+        y * self.params.width + x
+    }
+
+    /// Inverse of [Board::coords_to_index]
+    fn index_to_coords(&self, index: usize) -> (usize, usize) {
+        (index % self.params.width, index / self.params.width)
+    }
+
+    fn place_mines_and_hints(&mut self, x: usize, y: usize) {
+        let skip: Vec<_> = self
+            .neighbors(x, y)
+            .chain([(x, y)])
+            .map(|(xx, yy)| self.coords_to_index(xx, yy))
+            .collect();
+        let mut rng = self.seed.rng();
+
+        self.place_mines(&mut rng, &skip);
+        self.place_hints();
+
+        if self.params.generation != Generation::NoGuess {
+            return;
+        }
+
+        // rerolling keeps drawing from the same generator,
+        // so the accepted layout still only depends on the seed and first click
+        let mut effort = NO_GUESS_EFFORT;
+
+        for _ in 1..NO_GUESS_ATTEMPTS {
+            match solver::is_solvable_from(self, x, y, &mut effort) {
+                Some(true) => return,
+                Some(false) => (),
+                None => break,
+            }
+
+            for tile in &mut self.tiles {
+                tile.object = Object::Blank;
+            }
+            self.place_mines(&mut rng, &skip);
+            self.place_hints();
+        }
+
+        self.fallback = true;
+    }
+
+    /// Place mines on the field, as many on a tile as the [Distribution] allows.
+    ///
+    /// The `skip` argument contains board indices
+    /// that shall not have a mine placed in.
+    fn place_mines(&mut self, rng: &mut WyRand, skip: &[usize]) {
+        let stack = self.params.distribution.stack() as usize;
+
+        // every tile repeated once per mine it can hold,
+        // which with single mines picks from the tiles just as before
+        let mines = (0..self.tiles.len())
+            .filter(|i| !skip.contains(i))
+            .flat_map(|i| iter::repeat_n(i, stack))
+            .choose_multiple(rng, self.params.mines);
+        debug_assert_eq!(mines.len(), self.params.mines, "validated by Params::new");
+
+        for mine in mines {
+            let tile = &mut self.tiles[mine];
+            tile.object = Object::Mine(tile.mines() + 1);
+        }
+
+        self.mine_tiles = count_mine_tiles(&self.tiles);
+    }
+
+    fn place_hints(&mut self) {
+        for x in 0..self.params.width {
+            for y in 0..self.params.height {
+                let idx = self.coords_to_index(x, y);
+                if self.tiles[idx].is_mine() {
+                    continue;
+                }
+                let mine_count: usize = self
+                    .neighbors(x, y)
+                    .map(|(xx, yy)| self.tiles[self.coords_to_index(xx, yy)].mines() as usize)
+                    .sum();
+                if mine_count > 0 {
+                    self.tiles[idx].object = Object::Hint(mine_count as _);
+                }
+            }
+        }
+    }
+}
+
+/// Numbers of covered tiles and flagged mines.
+fn count_covers(tiles: &[Tile]) -> (usize, usize) {
+    let covered = tiles.iter().filter(|t| t.cover != Cover::Down).count();
+    let flags = tiles.iter().map(|t| t.flags() as usize).sum();
+
+    (covered, flags)
+}
+
+fn count_mine_tiles(tiles: &[Tile]) -> usize {
+    tiles.iter().filter(|t| t.is_mine()).count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Board with its mines placed as drawn, one row per string:
+    /// `#` is a covered safe tile, `.` an uncovered one and `*` a covered mine.
+    fn board(rows: &[&str]) -> Board {
+        let (width, height) = (rows[0].len(), rows.len());
+        let cells: Vec<_> = rows.iter().flat_map(|row| row.chars()).collect();
+        let params = Params {
+            width,
+            height,
+            mines: cells.iter().filter(|&&cell| cell == '*').count(),
+            ..BEGINNER
+        };
+
+        let mut board = Board::with_seed(params, Seed::from(0));
+        for (idx, &cell) in cells.iter().enumerate() {
+            if cell == '*' {
+                board.tiles[idx].object = Object::Mine(1);
+            }
+        }
+        board.place_hints();
+        board.mine_tiles = count_mine_tiles(&board.tiles);
+        board.placed = true;
+
+        for (idx, &cell) in cells.iter().enumerate() {
+            if cell == '.' {
+                board.set_cover(idx, Cover::Down);
+            }
+        }
+
+        board
+    }
+
+    /// Everything an undo has to restore.
+    fn snapshot(board: &Board) -> (Vec<Tile>, usize, usize, bool, bool) {
+        (
+            board.tiles.clone(),
+            board.covered,
+            board.flags,
+            board.placed,
+            board.defeat,
+        )
+    }
+
+    /// Check that `action` is undone and redone as a single step.
+    fn assert_single_step(board: &mut Board, action: impl FnOnce(&mut Board)) {
+        let before = snapshot(board);
+        action(board);
+        let after = snapshot(board);
+        assert_ne!(before, after, "the action changed nothing");
+
+        assert!(board.undo());
+        assert_eq!(snapshot(board), before);
+
+        assert!(board.redo());
+        assert_eq!(snapshot(board), after);
+    }
+
+    #[test]
+    fn flood_fill_is_a_single_step() {
+        let mut board = board(&["#####", "#####", "####*"]);
+
+        assert_single_step(&mut board, |board| board.handle_primary_action(0, 0));
+        assert_eq!(board.covered, 1);
+        assert_eq!(board.history.undo.len(), 1);
+    }
+
+    #[test]
+    fn chord_is_a_single_step() {
+        let mut board = board(&["*##", "#.#", "###"]);
+        board.handle_secondary_action(0, 0);
+
+        assert_single_step(&mut board, |board| board.handle_chord_action(1, 1));
+        assert_eq!(board.covered, 1);
+
+        // the flag placed before the chord is its own step
+        assert!(board.undo());
+        assert!(board.undo());
+        assert_eq!(board.flags(), 0);
+        assert!(!board.undo());
+    }
+
+    #[test]
+    fn each_mark_is_a_single_step() {
+        let mut board = board(&["*#"]);
+        board.set_mark_cycle(MarkCycle::FlagUnsure);
+
+        for _ in 0..3 {
+            assert_single_step(&mut board, |board| board.handle_secondary_action(0, 0));
+        }
+        assert_eq!(board.tile(0, 0).cover(), Cover::Up(Mark::None));
+
+        assert!(board.undo());
+        assert_eq!(board.tile(0, 0).cover(), Cover::Up(Mark::Unsure));
+        assert!(board.undo());
+        assert_eq!(board.tile(0, 0).cover(), Cover::Up(Mark::Flag(1)));
+        assert_eq!(board.flags(), 1);
+        assert!(board.undo());
+        assert_eq!(board.tile(0, 0).cover(), Cover::Up(Mark::None));
+        assert_eq!(board.flags(), 0);
+    }
+
+    #[test]
+    fn fatal_click_can_be_undone() {
+        let mut board = board(&["*#", "##"]);
+
+        assert_single_step(&mut board, |board| board.handle_primary_action(0, 0));
+        assert!(board.is_defeat());
+
+        assert!(board.undo());
+        assert!(!board.is_defeat());
+        assert_eq!(board.tile(0, 0).cover(), Cover::Up(Mark::None));
+
+        board.handle_primary_action(1, 1);
+        assert!(!board.is_defeat());
+    }
+
+    #[test]
+    fn undoing_the_first_click_takes_the_mines_back() {
+        let mut board = Board::with_seed(BEGINNER, Seed::from(7));
+
+        assert_single_step(&mut board, |board| board.handle_primary_action(4, 4));
+        let layout = board.tiles.clone();
+
+        assert!(board.undo());
+        assert!(!board.is_initialized());
+        assert_eq!(board.mine_tiles, 0);
+        assert!(board.tiles.iter().all(|&tile| tile == Tile::new()));
+
+        board.handle_primary_action(4, 4);
+        assert_eq!(board.tiles, layout);
+    }
+
+    fn first_click(params: Params, seed: u64, x: usize, y: usize) -> Vec<Tile> {
+        let mut board = Board::with_seed(params, Seed::from(seed));
+        board.handle_primary_action(x, y);
+        board.tiles
+    }
+
+    #[test]
+    fn same_seed_and_first_click_give_the_same_layout() {
+        for seed in 0..10 {
+            assert_eq!(
+                first_click(EXPERT, seed, 3, 5),
+                first_click(EXPERT, seed, 3, 5)
+            );
+        }
+
+        let no_guess = INTERMEDIATE.with_generation(Generation::NoGuess);
+        assert_eq!(
+            first_click(no_guess, 1, 8, 8),
+            first_click(no_guess, 1, 8, 8)
+        );
+    }
+
+    #[test]
+    fn different_seeds_give_different_layouts() {
+        assert_ne!(first_click(EXPERT, 1, 3, 5), first_click(EXPERT, 2, 3, 5));
+    }
+
+    #[test]
+    fn params_need_tiles() {
+        assert_eq!(Params::new(0, 8, 0), Err(ParamsError::ZeroDimensions));
+        assert_eq!(Params::new(8, 0, 0), Err(ParamsError::ZeroDimensions));
+        assert!(Params::new(1, 1, 0).is_ok());
+    }
+
+    #[test]
+    fn params_cannot_exceed_max_dimension() {
+        let too_large = MAX_DIMENSION + 1;
+
+        assert_eq!(Params::new(too_large, 8, 0), Err(ParamsError::TooLarge));
+        assert_eq!(Params::new(8, too_large, 0), Err(ParamsError::TooLarge));
+        assert!(Params::new(MAX_DIMENSION, MAX_DIMENSION, 0).is_ok());
+    }
+
+    #[test]
+    fn mines_leave_room_for_the_safe_zone() {
+        // the first click clears a 3x3 zone
+        assert_eq!(
+            Params::new(8, 8, 56),
+            Err(ParamsError::TooManyMines { max: 55 })
+        );
+        assert!(Params::new(8, 8, 55).is_ok());
+
+        // stacked tiles fit more mines
+        let stacked = BEGINNER
+            .with_distribution(Distribution::Stacked(2))
+            .unwrap();
+        assert_eq!(
+            stacked.with_mines(111),
+            Err(ParamsError::TooManyMines { max: 110 })
+        );
+
+        // a wider neighborhood clears more tiles
+        let radius = BEGINNER.with_neighborhood(Neighborhood::Radius(2)).unwrap();
+        assert_eq!(
+            radius.with_mines(40),
+            Err(ParamsError::TooManyMines { max: 39 })
+        );
+    }
+
+    #[test]
+    fn neighborhoods_need_neighbors_in_reach() {
+        let empty = Offsets::new([]).unwrap();
+
+        for neighborhood in [
+            Neighborhood::Radius(0),
+            Neighborhood::Radius(MAX_REACH as u8 + 1),
+            Neighborhood::Custom(empty),
+        ] {
+            assert_eq!(
+                BEGINNER.with_neighborhood(neighborhood),
+                Err(ParamsError::BadNeighborhood)
+            );
+        }
+    }
+
+    #[test]
+    fn stacks_are_limited() {
+        for stack in [0, 1, MAX_STACK + 1] {
+            assert_eq!(
+                BEGINNER.with_distribution(Distribution::Stacked(stack)),
+                Err(ParamsError::BadStack)
+            );
+        }
+    }
+
+    #[test]
+    fn wrapping_needs_room_and_even_hex_rows() {
+        let small = Params::new(2, 8, 0).unwrap();
+        assert_eq!(small.with_wrapping(true), Err(ParamsError::Unwrappable));
+        assert!(Params::new(3, 3, 0).unwrap().with_wrapping(true).is_ok());
+
+        // knight moves reach two tiles away
+        let knight = Params::new(4, 8, 0)
+            .unwrap()
+            .with_neighborhood(Neighborhood::Knight)
+            .unwrap();
+        assert_eq!(knight.with_wrapping(true), Err(ParamsError::Unwrappable));
+
+        let hex = Params::new(8, 7, 0)
+            .unwrap()
+            .with_topology(Topology::Hex)
+            .unwrap();
+        assert_eq!(hex.with_wrapping(true), Err(ParamsError::Unwrappable));
+        assert!(BEGINNER
+            .with_topology(Topology::Hex)
+            .and_then(|hex| hex.with_wrapping(true))
+            .is_ok());
+    }
+
+    #[test]
+    fn deserialized_params_are_checked() {
+        let params = |json: &str| serde_json::from_str::<Params>(json);
+
+        let classic = r#"{"width": 8, "height": 8, "mines": 10, "generation": "Random"}"#;
+        assert_eq!(params(classic).unwrap(), BEGINNER);
+
+        for invalid in [
+            r#"{"width": 0, "height": 8, "mines": 0, "generation": "Random"}"#,
+            r#"{"width": 8, "height": 8, "mines": 64, "generation": "Random"}"#,
+            r#"{"width": 8, "height": 7, "mines": 0, "generation": "Random",
+                "topology": "Hex", "wrapping": true}"#,
+            r#"{"width": 8, "height": 8, "mines": 0, "generation": "Random",
+                "neighborhood": {"Radius": 9}}"#,
+            r#"{"width": 8, "height": 8, "mines": 0, "generation": "Random",
+                "neighborhood": {"Custom": [[0, 0]]}}"#,
+        ] {
+            assert!(params(invalid).is_err(), "accepted {invalid}");
+        }
+    }
+}
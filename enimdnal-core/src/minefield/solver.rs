@@ -0,0 +1,893 @@
+//! Deductive minesweeper solver.
+//!
+//! The solver only ever looks at what the player can see:
+//! which tiles are still covered, the hints on the uncovered ones,
+//! and the total number of mines on the board.
+//! Player marks are deliberately ignored, since flags may well be misplaced.
+//!
+//! Reasoning is applied in increasing order of cost:
+//!
+//! 1. single hints, which on their own prove their covered neighbors safe or mined
+//! 2. pairs of hints, where the covered neighbors of one are a subset of the other's
+//! 3. full constraint propagation, enumerating every mine arrangement
+//!    consistent with all visible hints and the remaining mine count
+//!
+//! On boards with stacked mines, a tile is only decided once its number of mines is known.
+
+use std::collections::{HashMap, HashSet};
+
+use super::{Board, Cover, Object};
+
+/// Upper bound on the search steps spent enumerating a single group of tiles.
+///
+/// Groups that exceed it are left undecided rather than stalling the game.
+const ENUMERATION_BUDGET: usize = 250_000;
+
+/// Everything that can be proven about the covered tiles of a board.
+#[derive(Debug, Default, Clone)]
+pub struct Deductions {
+    /// Covered tiles that certainly do not hold a mine.
+    pub safe: HashSet<(usize, usize)>,
+
+    /// Covered tiles that certainly hold a mine, or a known number of stacked ones.
+    pub mines: HashSet<(usize, usize)>,
+}
+
+/// Board indices of the covered tiles proven so far.
+#[derive(Debug, Default, Clone)]
+struct Knowledge {
+    safe: HashSet<usize>,
+
+    /// Along with the number of mines on each.
+    mines: HashMap<usize, usize>,
+}
+
+/// Exactly `mines` mines lie on the `cells`.
+///
+/// Read off an uncovered hint tile, with the `cells`
+/// being its covered neighbors whose contents are not yet known.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct Constraint {
+    /// Sorted board indices.
+    cells: Vec<usize>,
+    mines: usize,
+}
+
+/// A single proven fact: the tile at a board index holds a number of mines, possibly none.
+type Finding = (usize, usize);
+
+/// Covered tiles linked together by shared hints, along with
+/// every arrangement of their mines that satisfies those hints.
+#[derive(Debug)]
+struct Component {
+    /// Board indices.
+    cells: Vec<usize>,
+
+    /// Indexed by the number of mines in the component.
+    outcomes: Vec<Outcome>,
+
+    /// Set when the enumeration ran out of budget,
+    /// in which case `outcomes` are incomplete and cannot be relied on.
+    exhausted: bool,
+
+    /// Search steps the enumeration took.
+    steps: usize,
+}
+
+/// Arrangements of a [Component] with a fixed number of mines.
+///
+/// Solutions are weighed by how likely the mine placement is to produce them,
+/// which only matters for stacked mines, see [super::Distribution::Stacked].
+#[derive(Debug, Clone)]
+struct Outcome {
+    solutions: f64,
+
+    /// Number of solutions in which each component cell holds a mine.
+    hits: Vec<f64>,
+
+    /// Numbers of mines each component cell holds across the solutions,
+    /// as bit sets indexed by the number.
+    counts: Vec<u8>,
+}
+
+/// Find every covered tile of the `board` that is provably safe or provably a mine.
+pub fn solve(board: &Board) -> Deductions {
+    let knowledge = deduce(board, Knowledge::default(), &mut 0);
+
+    let mines = knowledge.mines.keys().copied().collect();
+
+    Deductions {
+        safe: to_coords(board, &knowledge.safe),
+        mines: to_coords(board, &mines),
+    }
+}
+
+/// Chance of holding a mine, for every covered tile of the `board`.
+///
+/// Every arrangement of mines that agrees with the visible hints
+/// and the total mine count is taken to be equally likely,
+/// or as likely as the mine placement makes it with stacked mines.
+/// Covered tiles away from all hints share whatever mines are left over evenly,
+/// as do tiles of groups too large to enumerate.
+pub fn probabilities(board: &Board) -> HashMap<(usize, usize), f64> {
+    let knowledge = deduce(board, Knowledge::default(), &mut 0);
+    let constraints = constraints(board, &knowledge);
+    let stack = stack(board);
+
+    let mut chances: HashMap<usize, f64> = HashMap::new();
+    chances.extend(knowledge.safe.iter().map(|&idx| (idx, 0.)));
+    chances.extend(knowledge.mines.keys().map(|&idx| (idx, 1.)));
+
+    let components: Vec<_> = components(&constraints)
+        .into_iter()
+        .map(|(cells, constraints)| enumerate(cells, &constraints, stack))
+        .filter(|component| !component.exhausted)
+        .collect();
+
+    let frontier: HashSet<_> = components.iter().flat_map(|c| &c.cells).collect();
+    let interior: Vec<_> = (0..board.tiles.len())
+        .filter(|idx| is_covered(board, *idx) && !frontier.contains(idx))
+        .filter(|idx| !knowledge.safe.contains(idx) && !knowledge.mines.contains_key(idx))
+        .collect();
+
+    let remaining = board.mines().saturating_sub(knowledge.mines.values().sum());
+    let slots = interior.len() * stack;
+    let weights = interior_weights(slots, remaining);
+
+    // solution counts get huge, but only their ratios matter
+    let scales: Vec<_> = components
+        .iter()
+        .map(|c| c.outcomes.iter().map(|o| o.solutions).fold(1., f64::max))
+        .collect();
+    let distributions: Vec<Vec<_>> = components
+        .iter()
+        .zip(&scales)
+        .map(|(c, scale)| c.outcomes.iter().map(|o| o.solutions / scale).collect())
+        .collect();
+
+    let weigh = |ways: &[f64], shift: usize| -> f64 {
+        let shifted = weights.iter().skip(shift);
+        ways.iter()
+            .zip(shifted)
+            .map(|(ways, weight)| ways * weight)
+            .sum()
+    };
+
+    let all = distributions
+        .iter()
+        .fold(vec![1.], |acc, d| multiply(&acc, d, remaining));
+    let total = weigh(&all, 0);
+
+    if total == 0. {
+        return to_coords_map(board, chances);
+    }
+
+    for (i, component) in components.iter().enumerate() {
+        let others = distributions
+            .iter()
+            .enumerate()
+            .filter(|&(j, _)| j != i)
+            .fold(vec![1.], |acc, (_, d)| multiply(&acc, d, remaining));
+
+        for (k, outcome) in component.outcomes.iter().enumerate() {
+            let weight = weigh(&others, k) / scales[i] / total;
+
+            for (&idx, hits) in component.cells.iter().zip(&outcome.hits) {
+                *chances.entry(idx).or_default() += hits * weight;
+            }
+        }
+    }
+
+    if !interior.is_empty() {
+        let leftover: f64 = all
+            .iter()
+            .zip(&weights)
+            .enumerate()
+            .map(|(t, (ways, weight))| ways * weight * hit_chance(slots, stack, remaining - t))
+            .sum();
+        let chance = leftover / total;
+
+        chances.extend(interior.iter().map(|&idx| (idx, chance)));
+    }
+
+    to_coords_map(board, chances)
+}
+
+/// Check whether a player could clear the board by logic alone,
+/// starting from a single click on (`x`, `y`).
+///
+/// The board is expected to have its mines placed, with every tile still covered.
+/// The search steps taken are deducted from the `effort`,
+/// and running out of it leaves the answer unknown.
+pub(super) fn is_solvable_from(
+    board: &Board,
+    x: usize,
+    y: usize,
+    effort: &mut usize,
+) -> Option<bool> {
+    let mut board = board.clone();
+
+    board.uncover(x, y);
+
+    while !board.is_victory() {
+        let mut steps = 0;
+        let knowledge = deduce(&board, Knowledge::default(), &mut steps);
+        *effort = effort.checked_sub(steps)?;
+
+        if knowledge.safe.is_empty() {
+            return Some(false);
+        }
+
+        for idx in knowledge.safe {
+            let (xx, yy) = board.index_to_coords(idx);
+            if board.tile(xx, yy).is_uncoverable() {
+                board.uncover(xx, yy);
+            }
+        }
+    }
+
+    Some(true)
+}
+
+/// Keep applying the reasoning rules until nothing new can be proven,
+/// adding the search steps taken to `steps`.
+///
+/// Reading the hints off the board counts as a step per tile.
+fn deduce(board: &Board, mut knowledge: Knowledge, steps: &mut usize) -> Knowledge {
+    loop {
+        let constraints = constraints(board, &knowledge);
+        *steps += board.tiles.len();
+
+        let stack = stack(board);
+
+        let mut findings = single_hint(&constraints, stack);
+        if findings.is_empty() {
+            findings = subsets(&constraints, stack);
+        }
+        if findings.is_empty() {
+            findings = propagate(board, &knowledge, &constraints, steps);
+        }
+
+        if findings.is_empty() {
+            return knowledge;
+        }
+
+        for (idx, mines) in findings {
+            if mines == 0 {
+                knowledge.safe.insert(idx);
+            } else {
+                knowledge.mines.insert(idx, mines);
+            }
+        }
+    }
+}
+
+fn constraints(board: &Board, knowledge: &Knowledge) -> Vec<Constraint> {
+    let (width, height) = board.dims();
+    let mut constraints = vec![];
+
+    for y in 0..height {
+        for x in 0..width {
+            let Some(hint) = visible_hint(board, x, y) else {
+                continue;
+            };
+
+            let mut cells = vec![];
+            let mut mines = hint as usize;
+
+            for (xx, yy) in board.neighbors(x, y) {
+                let idx = board.coords_to_index(xx, yy);
+
+                if !is_covered(board, idx) || knowledge.safe.contains(&idx) {
+                    continue;
+                }
+
+                if let Some(known) = knowledge.mines.get(&idx) {
+                    mines = mines.saturating_sub(*known);
+                    continue;
+                }
+
+                cells.push(idx);
+            }
+
+            if cells.is_empty() {
+                continue;
+            }
+
+            cells.sort_unstable();
+            constraints.push(Constraint { cells, mines });
+        }
+    }
+
+    constraints.sort_unstable();
+    constraints.dedup();
+
+    constraints
+}
+
+/// A hint of zero proves all its cells safe,
+/// a hint filling every cell with a full `stack` of mines proves them all mines.
+fn single_hint(constraints: &[Constraint], stack: usize) -> Vec<Finding> {
+    let mut findings = vec![];
+
+    for constraint in constraints {
+        if constraint.mines == 0 {
+            findings.extend(constraint.cells.iter().map(|&idx| (idx, 0)));
+        } else if constraint.mines == constraint.cells.len() * stack {
+            findings.extend(constraint.cells.iter().map(|&idx| (idx, stack)));
+        }
+    }
+
+    findings
+}
+
+/// If the cells of one constraint are a subset of another's,
+/// the difference of the two holds exactly the difference of their mines.
+fn subsets(constraints: &[Constraint], stack: usize) -> Vec<Finding> {
+    let mut by_cell: HashMap<usize, Vec<&Constraint>> = HashMap::new();
+    for constraint in constraints {
+        for &idx in &constraint.cells {
+            by_cell.entry(idx).or_default().push(constraint);
+        }
+    }
+
+    let mut findings = vec![];
+
+    for small in constraints {
+        // any superset must share the first cell of the subset
+        let candidates = &by_cell[&small.cells[0]];
+
+        for &big in candidates {
+            if big.cells.len() <= small.cells.len() || !is_subset(&small.cells, &big.cells) {
+                continue;
+            }
+
+            let rest: Vec<_> = big
+                .cells
+                .iter()
+                .copied()
+                .filter(|idx| small.cells.binary_search(idx).is_err())
+                .collect();
+            let mines = big.mines.saturating_sub(small.mines);
+
+            if mines == 0 {
+                findings.extend(rest.iter().map(|&idx| (idx, 0)));
+            } else if mines == rest.len() * stack {
+                findings.extend(rest.iter().map(|&idx| (idx, stack)));
+            }
+        }
+    }
+
+    findings
+}
+
+/// Decide every tile for which all mine arrangements,
+/// consistent with the visible hints and the remaining mine count, agree.
+fn propagate(
+    board: &Board,
+    knowledge: &Knowledge,
+    constraints: &[Constraint],
+    steps: &mut usize,
+) -> Vec<Finding> {
+    let stack = stack(board);
+    let components: Vec<_> = components(constraints)
+        .into_iter()
+        .map(|(cells, constraints)| enumerate(cells, &constraints, stack))
+        .collect();
+    *steps += components.iter().map(|c| c.steps).sum::<usize>();
+
+    // covered tiles not bordering any hint, about which nothing is known locally
+    let frontier: HashSet<_> = constraints.iter().flat_map(|c| &c.cells).collect();
+    let interior: Vec<_> = (0..board.tiles.len())
+        .filter(|idx| is_covered(board, *idx) && !frontier.contains(idx))
+        .filter(|idx| !knowledge.safe.contains(idx) && !knowledge.mines.contains_key(idx))
+        .collect();
+
+    let remaining = board.mines().saturating_sub(knowledge.mines.values().sum());
+    let slots = interior.len() * stack;
+    let fits = |total: usize| total <= remaining && remaining - total <= slots;
+
+    let feasible: Vec<_> = components.iter().map(feasible_counts).collect();
+    let mut findings = vec![];
+
+    for (i, component) in components.iter().enumerate() {
+        if component.exhausted {
+            continue;
+        }
+
+        let others = feasible
+            .iter()
+            .enumerate()
+            .filter(|&(j, _)| j != i)
+            .fold(vec![true], |acc, (_, counts)| {
+                convolve(&acc, counts, remaining)
+            });
+        let possible: Vec<_> = (0..component.outcomes.len())
+            .filter(|&k| feasible[i][k])
+            .filter(|&k| (0..others.len()).any(|s| others[s] && fits(k + s)))
+            .collect();
+
+        if possible.is_empty() {
+            continue;
+        }
+
+        for (cell_no, &idx) in component.cells.iter().enumerate() {
+            let counts = possible
+                .iter()
+                .fold(0, |acc, &k| acc | component.outcomes[k].counts[cell_no]);
+
+            // decided once every solution puts the same number of mines there
+            if counts.is_power_of_two() {
+                findings.push((idx, counts.trailing_zeros() as usize));
+            }
+        }
+    }
+
+    if !interior.is_empty() {
+        let totals = feasible
+            .iter()
+            .fold(vec![true], |acc, counts| convolve(&acc, counts, remaining));
+        let fitting: Vec<_> = (0..totals.len())
+            .filter(|&t| totals[t] && fits(t))
+            .collect();
+
+        if fitting.is_empty() {
+            return findings;
+        }
+
+        if fitting.iter().all(|&t| t == remaining) {
+            findings.extend(interior.iter().map(|&idx| (idx, 0)));
+        } else if fitting.iter().all(|&t| remaining - t == slots) {
+            findings.extend(interior.iter().map(|&idx| (idx, stack)));
+        }
+    }
+
+    findings
+}
+
+/// Split the constraints into independent groups,
+/// linked by sharing at least one cell.
+fn components(constraints: &[Constraint]) -> Vec<(Vec<usize>, Vec<&Constraint>)> {
+    let mut groups: Vec<(HashSet<usize>, Vec<&Constraint>)> = vec![];
+
+    for constraint in constraints {
+        let (linked, mut rest): (Vec<_>, Vec<_>) = groups
+            .into_iter()
+            .partition(|(cells, _)| constraint.cells.iter().any(|idx| cells.contains(idx)));
+
+        let mut cells: HashSet<_> = constraint.cells.iter().copied().collect();
+        let mut members = vec![constraint];
+        for (linked_cells, linked_members) in linked {
+            cells.extend(linked_cells);
+            members.extend(linked_members);
+        }
+
+        rest.push((cells, members));
+        groups = rest;
+    }
+
+    groups
+        .into_iter()
+        .map(|(cells, constraints)| {
+            let mut cells: Vec<_> = cells.into_iter().collect();
+            cells.sort_unstable();
+            (cells, constraints)
+        })
+        .collect()
+}
+
+/// Backtracking search over every mine arrangement of a component,
+/// with up to `stack` mines on a cell.
+fn enumerate(cells: Vec<usize>, constraints: &[&Constraint], stack: usize) -> Component {
+    struct Search<'a> {
+        /// Constraints as (local cell numbers, mines).
+        constraints: Vec<(Vec<usize>, usize)>,
+        /// Constraint numbers affected by each local cell.
+        touching: Vec<Vec<usize>>,
+        /// Per constraint: (mines placed, cells left unassigned).
+        progress: Vec<(usize, usize)>,
+        /// Mines on each local cell.
+        assignment: Vec<usize>,
+        stack: usize,
+        /// Ways of placing each number of mines into the slots of a single cell.
+        ways: Vec<f64>,
+        outcomes: &'a mut [Outcome],
+        budget: usize,
+    }
+
+    impl Search<'_> {
+        fn run(&mut self, cell: usize) -> bool {
+            if self.budget == 0 {
+                return false;
+            }
+            self.budget -= 1;
+
+            if cell == self.assignment.len() {
+                let mines = self.assignment.iter().sum::<usize>();
+                let weight: f64 = self.assignment.iter().map(|&m| self.ways[m]).product();
+                let outcome = &mut self.outcomes[mines];
+                outcome.solutions += weight;
+                for (cell_no, &mines) in self.assignment.iter().enumerate() {
+                    if mines > 0 {
+                        outcome.hits[cell_no] += weight;
+                    }
+                    outcome.counts[cell_no] |= 1 << mines;
+                }
+                return true;
+            }
+
+            for mines in 0..=self.stack {
+                self.assignment[cell] = mines;
+                if self.place(cell, mines) && !self.run(cell + 1) {
+                    return false;
+                }
+                self.unplace(cell, mines);
+            }
+
+            true
+        }
+
+        /// Assign a cell, telling whether all its constraints can still be met.
+        fn place(&mut self, cell: usize, mines: usize) -> bool {
+            let mut valid = true;
+
+            for &c in &self.touching[cell] {
+                let (placed, unassigned) = &mut self.progress[c];
+                *placed += mines;
+                *unassigned -= 1;
+
+                let target = self.constraints[c].1;
+                valid &= *placed <= target && *placed + *unassigned * self.stack >= target;
+            }
+
+            valid
+        }
+
+        fn unplace(&mut self, cell: usize, mines: usize) {
+            for &c in &self.touching[cell] {
+                let (placed, unassigned) = &mut self.progress[c];
+                *placed -= mines;
+                *unassigned += 1;
+            }
+        }
+    }
+
+    let cells = search_order(cells, constraints);
+    let local: HashMap<_, _> = cells.iter().enumerate().map(|(i, &idx)| (idx, i)).collect();
+
+    let constraints: Vec<_> = constraints
+        .iter()
+        .map(|c| {
+            (
+                c.cells.iter().map(|idx| local[idx]).collect::<Vec<_>>(),
+                c.mines,
+            )
+        })
+        .collect();
+    let mut touching = vec![vec![]; cells.len()];
+    for (c, (members, _)) in constraints.iter().enumerate() {
+        for &cell in members {
+            touching[cell].push(c);
+        }
+    }
+    let progress = constraints.iter().map(|(m, _)| (0, m.len())).collect();
+
+    let empty = Outcome {
+        solutions: 0.,
+        hits: vec![0.; cells.len()],
+        counts: vec![0; cells.len()],
+    };
+    let mut outcomes = vec![empty; cells.len() * stack + 1];
+
+    let mut search = Search {
+        constraints,
+        touching,
+        progress,
+        assignment: vec![0; cells.len()],
+        stack,
+        ways: (0..=stack).map(|m| ln_binomial(stack, m).exp()).collect(),
+        outcomes: &mut outcomes,
+        budget: ENUMERATION_BUDGET,
+    };
+    let exhausted = !search.run(0);
+    let steps = ENUMERATION_BUDGET - search.budget;
+
+    Component {
+        cells,
+        outcomes,
+        exhausted,
+        steps,
+    }
+}
+
+/// Order cells so that neighboring ones are assigned one after another,
+/// letting the search detect violated constraints early.
+fn search_order(cells: Vec<usize>, constraints: &[&Constraint]) -> Vec<usize> {
+    let mut ordered = Vec::with_capacity(cells.len());
+    let mut seen = HashSet::new();
+
+    for constraint in constraints {
+        for &idx in &constraint.cells {
+            if seen.insert(idx) {
+                ordered.push(idx);
+            }
+        }
+    }
+
+    debug_assert_eq!(ordered.len(), cells.len());
+    ordered
+}
+
+/// Mine counts a component can take, indexed by the count.
+fn feasible_counts(component: &Component) -> Vec<bool> {
+    if component.exhausted {
+        return vec![true; component.outcomes.len()];
+    }
+
+    component
+        .outcomes
+        .iter()
+        .map(|outcome| outcome.solutions > 0.)
+        .collect()
+}
+
+/// Sums reachable by adding one value from each set, up to `limit` inclusive.
+///
+/// Sets are represented as flags indexed by the value.
+fn convolve(a: &[bool], b: &[bool], limit: usize) -> Vec<bool> {
+    let len = usize::min(a.len() + b.len() - 1, limit + 1);
+    let mut sums = vec![false; len];
+
+    for (i, _) in a.iter().enumerate().filter(|(_, &x)| x) {
+        for (j, _) in b.iter().enumerate().filter(|(_, &y)| y) {
+            if let Some(sum) = sums.get_mut(i + j) {
+                *sum = true;
+            }
+        }
+    }
+
+    sums
+}
+
+/// Relative number of ways to fit the leftover mines into the `slots` of the interior,
+/// indexed by the number of mines placed on the frontier.
+fn interior_weights(slots: usize, remaining: usize) -> Vec<f64> {
+    let ln_ways: Vec<_> = (0..=remaining)
+        .map(|frontier| {
+            let leftover = remaining - frontier;
+            (leftover <= slots).then(|| ln_binomial(slots, leftover))
+        })
+        .collect();
+    let max = ln_ways.iter().flatten().copied().fold(f64::MIN, f64::max);
+
+    ln_ways
+        .into_iter()
+        .map(|ln| ln.map_or(0., |ln| f64::exp(ln - max)))
+        .collect()
+}
+
+/// Chance that a single interior tile of `stack` out of all the `slots`
+/// ends up with any of the `mines` spread over them.
+fn hit_chance(slots: usize, stack: usize, mines: usize) -> f64 {
+    if mines > slots - stack {
+        return 1.;
+    }
+
+    1. - f64::exp(ln_binomial(slots - stack, mines) - ln_binomial(slots, mines))
+}
+
+/// Natural logarithm of the binomial coefficient, which overflows far too easily otherwise.
+fn ln_binomial(n: usize, k: usize) -> f64 {
+    (1..=k)
+        .map(|i| f64::ln((n - k + i) as f64 / i as f64))
+        .sum()
+}
+
+/// Product of two polynomials given by their coefficients, up to degree `limit` inclusive.
+fn multiply(a: &[f64], b: &[f64], limit: usize) -> Vec<f64> {
+    let len = usize::min(a.len() + b.len() - 1, limit + 1);
+    let mut product = vec![0.; len];
+
+    for (i, x) in a.iter().enumerate() {
+        for (j, y) in b.iter().enumerate() {
+            if let Some(coeff) = product.get_mut(i + j) {
+                *coeff += x * y;
+            }
+        }
+    }
+
+    product
+}
+
+/// Both slices are expected to be sorted.
+fn is_subset(small: &[usize], big: &[usize]) -> bool {
+    small.iter().all(|idx| big.binary_search(idx).is_ok())
+}
+
+/// The hint shown on an uncovered tile, blanks being zeroes.
+fn visible_hint(board: &Board, x: usize, y: usize) -> Option<u8> {
+    let tile = board.tile(x, y);
+
+    match (tile.cover, tile.object) {
+        (Cover::Down, Object::Hint(hint)) => Some(hint),
+        (Cover::Down, Object::Blank) => Some(0),
+        _ => None,
+    }
+}
+
+/// Most mines a tile of the `board` can hold.
+fn stack(board: &Board) -> usize {
+    board.params().distribution().stack() as usize
+}
+
+fn is_covered(board: &Board, idx: usize) -> bool {
+    matches!(board.tiles[idx].cover, Cover::Up(_))
+}
+
+fn to_coords(board: &Board, indices: &HashSet<usize>) -> HashSet<(usize, usize)> {
+    indices
+        .iter()
+        .map(|&idx| board.index_to_coords(idx))
+        .collect()
+}
+
+fn to_coords_map<T>(board: &Board, map: HashMap<usize, T>) -> HashMap<(usize, usize), T> {
+    map.into_iter()
+        .map(|(idx, value)| (board.index_to_coords(idx), value))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::minefield::{count_mine_tiles, Distribution, Params, BEGINNER};
+    use crate::random::Seed;
+
+    /// Board with its mines placed as drawn, one row per string:
+    /// `#` is a covered safe tile, `.` an uncovered one,
+    /// and `*`, `2` or `3` a covered tile with that many mines.
+    fn board(rows: &[&str], stack: u8) -> Board {
+        let (width, height) = (rows[0].len(), rows.len());
+        let cells: Vec<_> = rows.iter().flat_map(|row| row.chars()).collect();
+        let mines_at = |cell: char| match cell {
+            '*' => 1,
+            digit => digit.to_digit(10).unwrap_or(0) as u8,
+        };
+
+        let distribution = match stack {
+            1 => Distribution::Single,
+            stack => Distribution::Stacked(stack),
+        };
+        let params = Params {
+            width,
+            height,
+            mines: cells.iter().map(|&c| mines_at(c) as usize).sum(),
+            distribution,
+            ..BEGINNER
+        };
+
+        let mut board = Board::with_seed(params, Seed::from(0));
+        for (idx, &cell) in cells.iter().enumerate() {
+            if mines_at(cell) > 0 {
+                board.tiles[idx].object = Object::Mine(mines_at(cell));
+            }
+        }
+        board.place_hints();
+        board.mine_tiles = count_mine_tiles(&board.tiles);
+        board.placed = true;
+
+        for (idx, &cell) in cells.iter().enumerate() {
+            if cell == '.' {
+                board.set_cover(idx, Cover::Down);
+            }
+        }
+
+        board
+    }
+
+    fn coords(tiles: &[(usize, usize)]) -> HashSet<(usize, usize)> {
+        tiles.iter().copied().collect()
+    }
+
+    #[test]
+    fn single_hint_proves_mines_and_safe_tiles() {
+        let board = board(&["*..#"], 1);
+        let constraints = constraints(&board, &Knowledge::default());
+
+        let mut findings = single_hint(&constraints, 1);
+        findings.sort_unstable();
+
+        assert_eq!(findings, vec![(0, 1), (3, 0)]);
+    }
+
+    #[test]
+    fn subset_of_hints_proves_the_difference() {
+        let board = board(&["#*#", "...", "..."], 1);
+        let constraints = constraints(&board, &Knowledge::default());
+        assert!(single_hint(&constraints, 1).is_empty());
+
+        let mut findings = subsets(&constraints, 1);
+        findings.sort_unstable();
+        findings.dedup();
+
+        let idx = |x, y| board.coords_to_index(x, y);
+        assert_eq!(findings, vec![(idx(0, 0), 0), (idx(2, 0), 0)]);
+    }
+
+    #[test]
+    fn mine_count_decides_tiles_away_from_hints() {
+        let Deductions { safe, mines } = solve(&board(&["*", ".", "#", "#", "#"], 1));
+        assert_eq!(safe, coords(&[(0, 3), (0, 4)]));
+        assert!(mines.is_empty());
+
+        let Deductions { safe, mines } = solve(&board(&["*", ".", "#", "*", "*"], 1));
+        assert!(safe.is_empty());
+        assert_eq!(mines, coords(&[(0, 3), (0, 4)]));
+    }
+
+    #[test]
+    fn stacked_mines_are_counted() {
+        let board = board(&["2..*"], 2);
+        let knowledge = deduce(&board, Knowledge::default(), &mut 0);
+
+        // a hint of 2 on a single tile is a full stack, a hint of 1 only a part of one
+        assert_eq!(knowledge.mines.get(&0), Some(&2));
+        assert_eq!(knowledge.mines.get(&3), Some(&1));
+        assert!(knowledge.safe.is_empty());
+    }
+
+    #[test]
+    fn stacked_hint_filling_its_tiles_proves_full_stacks() {
+        let board = board(&["3.3"], 3);
+        let constraints = constraints(&board, &Knowledge::default());
+
+        let mut findings = single_hint(&constraints, 3);
+        findings.sort_unstable();
+        assert_eq!(findings, vec![(0, 3), (2, 3)]);
+    }
+
+    #[test]
+    fn probabilities_add_up_to_the_mines() {
+        let board = board(&["*", ".", "#", "#", "#"], 1);
+        let chances = probabilities(&board);
+
+        assert!((chances[&(0, 0)] - 0.5).abs() < 1e-9);
+        assert!((chances[&(0, 2)] - 0.5).abs() < 1e-9);
+        assert_eq!(chances[&(0, 3)], 0.);
+
+        for seed in 0..20 {
+            let mut board = Board::with_seed(BEGINNER, Seed::from(seed));
+            board.handle_primary_action(4, 4);
+            if board.is_victory() {
+                continue;
+            }
+
+            let total: f64 = probabilities(&board).values().sum();
+            assert!(
+                (total - board.mines() as f64).abs() < 1e-6,
+                "seed {seed}: {total}"
+            );
+        }
+    }
+
+    #[test]
+    fn solvable_layout_is_solvable() {
+        let board = board(&["#*#", "###", "###"], 1);
+        let mut effort = usize::MAX;
+        assert_eq!(is_solvable_from(&board, 0, 2, &mut effort), Some(true));
+    }
+
+    #[test]
+    fn fifty_fifty_is_not_solvable() {
+        let board = board(&["*#", "##", "##"], 1);
+        let mut effort = usize::MAX;
+        assert_eq!(is_solvable_from(&board, 0, 2, &mut effort), Some(false));
+    }
+
+    #[test]
+    fn running_out_of_effort_leaves_solvability_unknown() {
+        let board = board(&["#*#", "###", "###"], 1);
+        assert_eq!(is_solvable_from(&board, 0, 2, &mut 0), None);
+    }
+}
@@ -0,0 +1,95 @@
+//! Measures of how much work it takes to clear a board.
+
+use std::collections::HashSet;
+
+use super::{Board, Cover};
+
+/// Bechtel's Board Benchmark Value, the least number of clicks
+/// needed to clear the board without chording or flagging.
+///
+/// Every opening (connected area of blank tiles) takes one click,
+/// as does every hint tile not bordering an opening.
+/// Only meaningful once the mines have been placed.
+pub fn bbbv(board: &Board) -> usize {
+    count_bbbv(board, false)
+}
+
+/// Part of the [bbbv] already cleared by the player,
+/// equal to the whole of it on a won board.
+pub fn solved_bbbv(board: &Board) -> usize {
+    count_bbbv(board, true)
+}
+
+/// Per-second rate of clearing the [bbbv], the usual measure of speed.
+pub fn bbbv_per_second(bbbv: usize, time_milisec: u32) -> f64 {
+    if time_milisec == 0 {
+        return 0.;
+    }
+
+    bbbv as f64 * 1000. / time_milisec as f64
+}
+
+/// Share of the clicks that counted towards the [bbbv] of the board.
+pub fn efficiency(bbbv: usize, clicks: usize) -> f64 {
+    if clicks == 0 {
+        return 0.;
+    }
+
+    bbbv as f64 / clicks as f64
+}
+
+fn count_bbbv(board: &Board, only_uncovered: bool) -> usize {
+    let (width, height) = board.dims();
+    let mut opened = HashSet::new();
+    let mut value = 0;
+
+    for y in 0..height {
+        for x in 0..width {
+            if !board.tile(x, y).is_blank() || opened.contains(&(x, y)) {
+                continue;
+            }
+
+            let uncovered = open(board, (x, y), &mut opened);
+            if uncovered || !only_uncovered {
+                value += 1;
+            }
+        }
+    }
+
+    for y in 0..height {
+        for x in 0..width {
+            let tile = board.tile(x, y);
+            let counts = !only_uncovered || tile.cover() == Cover::Down;
+            if tile.is_hint() && !opened.contains(&(x, y)) && counts {
+                value += 1;
+            }
+        }
+    }
+
+    value
+}
+
+/// Mark the opening at `start` along with its bordering hints as opened.
+///
+/// Returns whether the player has uncovered the opening.
+fn open(board: &Board, start: (usize, usize), opened: &mut HashSet<(usize, usize)>) -> bool {
+    let mut stack = vec![start];
+    let mut uncovered = false;
+    opened.insert(start);
+
+    while let Some((x, y)) = stack.pop() {
+        uncovered |= board.tile(x, y).cover() == Cover::Down;
+
+        for pos @ (xx, yy) in board.neighbors(x, y) {
+            if !opened.insert(pos) {
+                continue;
+            }
+
+            if board.tile(xx, yy).is_blank() {
+                stack.push(pos);
+            }
+        }
+    }
+
+    uncovered
+}
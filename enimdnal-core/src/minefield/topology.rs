@@ -0,0 +1,575 @@
+use serde::{Deserialize, Serialize};
+
+use super::{Params, ParamsError};
+
+/// Furthest a neighbor can be from a tile, in steps along either axis.
+pub const MAX_REACH: isize = 3;
+
+/// Built-in neighborhoods, from the classic one to the most far-reaching.
+pub const NEIGHBORHOOD_PRESETS: [Neighborhood; 5] = [
+    Neighborhood::Adjacent,
+    Neighborhood::Orthogonal,
+    Neighborhood::Knight,
+    Neighborhood::Radius(2),
+    Neighborhood::Radius(3),
+];
+
+/// Shape of the tiles, which decides what tiles neighbor each other.
+///
+/// Tiles are addressed by column and row either way.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default, Serialize, Deserialize)]
+pub enum Topology {
+    /// Square tiles, touching the eight around them by edges and corners.
+    #[default]
+    Square,
+
+    /// Pointy-topped hexagons with six neighbors each,
+    /// every odd row shifted right by half a tile.
+    Hex,
+}
+
+/// Which tiles count as the neighbors of a tile:
+/// the ones its hint counts the mines of, and that flood fills and chording reach.
+///
+/// Offsets between tiles are in columns and rows on square boards.
+/// On hex boards they are steps along a row and steps down and to the right,
+/// so that e.g. `(-1, 1)` is the tile down and to the left.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default, Serialize, Deserialize)]
+pub enum Neighborhood {
+    /// Every tile touching, by an edge or a corner.
+    #[default]
+    Adjacent,
+
+    /// Only the tiles sharing an edge, which on hex boards are all the touching ones.
+    Orthogonal,
+
+    /// Tiles a chess knight's move away, jumping over the ones in between.
+    ///
+    /// On hex boards, as in hexagonal chess, that is two steps in one direction
+    /// and one more turning off it.
+    Knight,
+
+    /// Every tile up to the given number of steps away, from 1 to [MAX_REACH].
+    Radius(u8),
+
+    /// Any set of offsets, for designing variants.
+    Custom(Offsets),
+}
+
+/// Set of offsets up to [MAX_REACH] steps away, in the coordinates of a [Neighborhood].
+///
+/// Every offset comes along with its opposite, so that neighboring goes both ways.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+#[serde(into = "Vec<(isize, isize)>", try_from = "Vec<(isize, isize)>")]
+pub struct Offsets(u64);
+
+/// Neighbors of the tiles of a particular board, worked out once up front.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Adjacency {
+    /// Offsets in columns and rows from the tiles of even and odd rows.
+    offsets: [Vec<(isize, isize)>; 2],
+    dims: (usize, usize),
+    wrapping: bool,
+}
+
+impl Topology {
+    /// Number of steps between tiles `offset` apart, going from neighbor to neighbor.
+    fn distance(self, (dx, dy): (isize, isize)) -> isize {
+        match self {
+            Topology::Square => isize::max(dx.abs(), dy.abs()),
+            Topology::Hex => dx.abs().max(dy.abs()).max((dx + dy).abs()),
+        }
+    }
+
+    /// Offset in columns and rows from a tile in a row of `parity` to the tile `offset` away.
+    fn to_grid(self, (dx, dy): (isize, isize), parity: isize) -> (isize, isize) {
+        match self {
+            Topology::Square => (dx, dy),
+            // rows alternate between leaning left and right,
+            // which decides the column of the tiles straight down and to the right
+            Topology::Hex => (dx + (parity + dy).div_euclid(2), dy),
+        }
+    }
+
+    /// Inverse of [Topology::to_grid].
+    fn to_offset(self, (dx, dy): (isize, isize), parity: isize) -> (isize, isize) {
+        match self {
+            Topology::Square => (dx, dy),
+            Topology::Hex => (dx - (parity + dy).div_euclid(2), dy),
+        }
+    }
+
+    /// Number of steps from the tile at `from` to the one at `to`, going from neighbor to neighbor.
+    ///
+    /// With `wrap` set to the board dimensions, the way across the edges counts too.
+    pub(super) fn steps(
+        self,
+        (x, y): (usize, usize),
+        (to_x, to_y): (usize, usize),
+        wrap: Option<(usize, usize)>,
+    ) -> usize {
+        let (dx, dy) = (to_x as isize - x as isize, to_y as isize - y as isize);
+        let (width, height) = wrap.map_or((0, 0), |(w, h)| (w as isize, h as isize));
+        let parity = (y % 2) as isize;
+
+        [-width, 0, width]
+            .into_iter()
+            .flat_map(|shift_x| [-height, 0, height].map(|shift_y| (dx + shift_x, dy + shift_y)))
+            .map(|grid| self.distance(self.to_offset(grid, parity)))
+            .min()
+            .unwrap_or(0) as usize
+    }
+}
+
+impl Neighborhood {
+    /// Whether the tile `offset` away is a neighbor on a board of `topology`.
+    fn contains(self, topology: Topology, offset @ (dx, dy): (isize, isize)) -> bool {
+        let distance = topology.distance(offset);
+
+        match (self, topology) {
+            (Neighborhood::Adjacent, _) => distance == 1,
+            (Neighborhood::Orthogonal, Topology::Square) => dx.abs() + dy.abs() == 1,
+            (Neighborhood::Orthogonal, Topology::Hex) => distance == 1,
+            (Neighborhood::Knight, Topology::Square) => {
+                matches!((dx.abs(), dy.abs()), (1, 2) | (2, 1))
+            }
+            // three steps away, but not in a straight line
+            (Neighborhood::Knight, Topology::Hex) => {
+                distance == 3 && dx != 0 && dy != 0 && dx + dy != 0
+            }
+            (Neighborhood::Radius(radius), _) => (1..=radius as isize).contains(&distance),
+            (Neighborhood::Custom(offsets), _) => offsets.contains(offset),
+        }
+    }
+
+    fn offsets(self, topology: Topology) -> impl Iterator<Item = (isize, isize)> {
+        within_reach().filter(move |&offset| self.contains(topology, offset))
+    }
+
+    /// Whether there is at least one neighbor, and none too far to reach.
+    pub(super) fn is_valid(self) -> bool {
+        match self {
+            Neighborhood::Radius(radius) => (1..=MAX_REACH).contains(&(radius as isize)),
+            Neighborhood::Custom(offsets) => offsets.0 != 0,
+            _ => true,
+        }
+    }
+}
+
+impl Offsets {
+    /// Set of the given offsets, and their opposites.
+    ///
+    /// Fails if an offset is zero or more than [MAX_REACH] steps away along an axis.
+    pub fn new(offsets: impl IntoIterator<Item = (isize, isize)>) -> Result<Self, ParamsError> {
+        let mut bits = 0;
+
+        for (dx, dy) in offsets {
+            let in_reach = dx.abs() <= MAX_REACH && dy.abs() <= MAX_REACH;
+            if !in_reach || (dx, dy) == (0, 0) {
+                return Err(ParamsError::BadNeighborhood);
+            }
+
+            bits |= bit((dx, dy)) | bit((-dx, -dy));
+        }
+
+        Ok(Self(bits))
+    }
+
+    pub fn contains(self, (dx, dy): (isize, isize)) -> bool {
+        let in_reach = dx.abs() <= MAX_REACH && dy.abs() <= MAX_REACH;
+        in_reach && self.0 & bit((dx, dy)) != 0
+    }
+
+    pub fn iter(self) -> impl Iterator<Item = (isize, isize)> {
+        within_reach().filter(move |&offset| self.contains(offset))
+    }
+}
+
+impl From<Offsets> for Vec<(isize, isize)> {
+    fn from(value: Offsets) -> Self {
+        value.iter().collect()
+    }
+}
+
+impl TryFrom<Vec<(isize, isize)>> for Offsets {
+    type Error = ParamsError;
+
+    fn try_from(value: Vec<(isize, isize)>) -> Result<Self, Self::Error> {
+        Self::new(value)
+    }
+}
+
+impl Adjacency {
+    pub(super) fn new(params: &Params) -> Self {
+        let (topology, neighborhood) = (params.topology, params.neighborhood);
+        let row_offsets = |parity| {
+            neighborhood
+                .offsets(topology)
+                .map(|offset| topology.to_grid(offset, parity))
+                .collect()
+        };
+
+        Self {
+            offsets: [row_offsets(0), row_offsets(1)],
+            dims: (params.width, params.height),
+            wrapping: params.wrapping,
+        }
+    }
+
+    /// Neighbors of the tile at `x`, `y`.
+    ///
+    /// On wrapping boards, the neighbors past an edge are found on the opposite one.
+    pub(super) fn neighbors(
+        &self,
+        x: usize,
+        y: usize,
+    ) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let (width, height) = self.dims;
+
+        self.offsets[y % 2]
+            .iter()
+            .filter_map(move |&(off_x, off_y)| {
+                if self.wrapping {
+                    return Some((wrap(x, off_x, width), wrap(y, off_y, height)));
+                }
+
+                let new_x = x.checked_add_signed(off_x)?;
+                let new_y = y.checked_add_signed(off_y)?;
+
+                (new_x < width && new_y < height).then_some((new_x, new_y))
+            })
+    }
+
+    /// Furthest the neighbors are, in columns and rows.
+    pub(super) fn reach(&self) -> (usize, usize) {
+        let offsets = self.offsets.iter().flatten();
+        let reach_x = offsets.clone().map(|(dx, _)| dx.unsigned_abs()).max();
+        let reach_y = offsets.map(|(_, dy)| dy.unsigned_abs()).max();
+
+        (reach_x.unwrap_or(0), reach_y.unwrap_or(0))
+    }
+
+    /// Most tiles a tile and its neighbors can cover,
+    /// which is the size of the largest possible safe zone around the first click.
+    pub(super) fn largest_zone(&self) -> usize {
+        let (width, height) = self.dims;
+        let (reach_x, reach_y) = self.reach();
+
+        // a tile far enough from the edges has all its neighbors on the board,
+        // so only the tiles up to there need checking,
+        // including one more row, as the rows alternate on hex boards
+        let xs = 0..usize::min(width, reach_x * 2 + 1);
+        let ys = 0..usize::min(height, reach_y * 2 + 2);
+
+        ys.flat_map(|y| xs.clone().map(move |x| (x, y)))
+            .map(|(x, y)| 1 + self.neighbors(x, y).count())
+            .max()
+            .unwrap_or(1)
+    }
+}
+
+/// Every offset up to [MAX_REACH] steps away along both axes.
+fn within_reach() -> impl Iterator<Item = (isize, isize)> {
+    let steps = -MAX_REACH..=MAX_REACH;
+
+    steps
+        .clone()
+        .flat_map(move |dx| steps.clone().map(move |dy| (dx, dy)))
+        .filter(|&offset| offset != (0, 0))
+}
+
+fn bit((dx, dy): (isize, isize)) -> u64 {
+    let side = MAX_REACH * 2 + 1;
+    let index = (dy + MAX_REACH) * side + dx + MAX_REACH;
+
+    1 << index
+}
+
+/// Move `by` tiles from `pos`, coming back around past either end of `len` tiles.
+fn wrap(pos: usize, by: isize, len: usize) -> usize {
+    (pos as isize + by).rem_euclid(len as isize) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::minefield::BEGINNER;
+
+    fn params(width: usize, height: usize, topology: Topology) -> Params {
+        Params {
+            width,
+            height,
+            mines: 0,
+            topology,
+            ..BEGINNER
+        }
+    }
+
+    fn neighbors(params: &Params, x: usize, y: usize) -> HashSet<(usize, usize)> {
+        Adjacency::new(params).neighbors(x, y).collect()
+    }
+
+    #[test]
+    fn square_offsets_are_columns_and_rows() {
+        for parity in [0, 1] {
+            assert_eq!(Topology::Square.to_grid((-1, 1), parity), (-1, 1));
+            assert_eq!(Topology::Square.to_grid((2, -3), parity), (2, -3));
+        }
+    }
+
+    #[test]
+    fn hex_offsets_lean_with_the_row() {
+        let hex = Topology::Hex;
+
+        // even rows lean left of the rows around them
+        assert_eq!(hex.to_grid((0, 1), 0), (0, 1));
+        assert_eq!(hex.to_grid((-1, 1), 0), (-1, 1));
+        assert_eq!(hex.to_grid((0, -1), 0), (-1, -1));
+        assert_eq!(hex.to_grid((1, -1), 0), (0, -1));
+
+        // odd rows lean right
+        assert_eq!(hex.to_grid((0, 1), 1), (1, 1));
+        assert_eq!(hex.to_grid((-1, 1), 1), (0, 1));
+        assert_eq!(hex.to_grid((0, -1), 1), (0, -1));
+        assert_eq!(hex.to_grid((1, -1), 1), (1, -1));
+
+        // two rows down is back in line
+        for parity in [0, 1] {
+            assert_eq!(hex.to_grid((-1, 2), parity), (0, 2));
+        }
+    }
+
+    #[test]
+    fn hex_tiles_have_six_neighbors() {
+        let hex = params(8, 8, Topology::Hex);
+
+        assert_eq!(
+            neighbors(&hex, 3, 2),
+            HashSet::from([(2, 2), (4, 2), (2, 1), (3, 1), (2, 3), (3, 3)])
+        );
+        assert_eq!(
+            neighbors(&hex, 3, 3),
+            HashSet::from([(2, 3), (4, 3), (3, 2), (4, 2), (3, 4), (4, 4)])
+        );
+    }
+
+    #[test]
+    fn steps_follow_the_topology() {
+        let square = Topology::Square;
+        assert_eq!(square.steps((2, 2), (2, 2), None), 0);
+        assert_eq!(square.steps((2, 2), (5, 3), None), 3);
+        assert_eq!(square.steps((0, 0), (7, 7), None), 7);
+
+        let hex = Topology::Hex;
+        assert_eq!(hex.steps((3, 2), (3, 3), None), 1);
+        assert_eq!(hex.steps((3, 2), (4, 3), None), 2);
+        assert_eq!(hex.steps((0, 0), (0, 2), None), 2);
+        assert_eq!(hex.steps((0, 0), (3, 0), None), 3);
+    }
+
+    #[test]
+    fn steps_take_the_way_across_the_seam() {
+        assert_eq!(Topology::Square.steps((0, 0), (7, 7), Some((8, 8))), 1);
+        assert_eq!(Topology::Square.steps((1, 4), (6, 4), Some((8, 8))), 3);
+        assert_eq!(Topology::Hex.steps((0, 0), (7, 7), Some((8, 8))), 1);
+        assert_eq!(Topology::Hex.steps((0, 0), (0, 6), Some((8, 8))), 2);
+    }
+
+    #[test]
+    fn adjacent_tiles_are_one_step_apart() {
+        for topology in [Topology::Square, Topology::Hex] {
+            for wrapping in [false, true] {
+                let params = Params {
+                    wrapping,
+                    ..params(8, 8, topology)
+                };
+                let adjacency = Adjacency::new(&params);
+                let wrap = wrapping.then_some((8, 8));
+                let tiles = || (0..8).flat_map(|y| (0..8).map(move |x| (x, y)));
+
+                for from in tiles() {
+                    let around: HashSet<_> = adjacency.neighbors(from.0, from.1).collect();
+
+                    for to in tiles() {
+                        assert_eq!(
+                            topology.steps(from, to, wrap) == 1,
+                            around.contains(&to),
+                            "{topology:?} from {from:?} to {to:?}"
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn hex_corners_depend_on_the_row() {
+        let hex = params(8, 8, Topology::Hex);
+
+        assert_eq!(neighbors(&hex, 0, 0), HashSet::from([(1, 0), (0, 1)]));
+        assert_eq!(
+            neighbors(&hex, 7, 0),
+            HashSet::from([(6, 0), (6, 1), (7, 1)])
+        );
+        assert_eq!(
+            neighbors(&hex, 0, 7),
+            HashSet::from([(1, 7), (0, 6), (1, 6)])
+        );
+        assert_eq!(neighbors(&hex, 7, 7), HashSet::from([(6, 7), (7, 6)]));
+    }
+
+    #[test]
+    fn wrapping_reaches_across_the_seam() {
+        let square = Params {
+            wrapping: true,
+            ..params(8, 8, Topology::Square)
+        };
+        assert_eq!(
+            neighbors(&square, 0, 0),
+            HashSet::from([
+                (7, 7),
+                (0, 7),
+                (1, 7),
+                (7, 0),
+                (1, 0),
+                (7, 1),
+                (0, 1),
+                (1, 1),
+            ])
+        );
+
+        let hex = Params {
+            wrapping: true,
+            ..params(8, 8, Topology::Hex)
+        };
+        assert_eq!(
+            neighbors(&hex, 0, 0),
+            HashSet::from([(7, 0), (1, 0), (7, 7), (0, 7), (7, 1), (0, 1)])
+        );
+        assert_eq!(
+            neighbors(&hex, 7, 7),
+            HashSet::from([(6, 7), (0, 7), (7, 6), (0, 6), (7, 0), (0, 0)])
+        );
+    }
+
+    #[test]
+    fn every_wrapping_tile_has_all_its_neighbors() {
+        for topology in [Topology::Square, Topology::Hex] {
+            for neighborhood in NEIGHBORHOOD_PRESETS {
+                let params = Params {
+                    wrapping: true,
+                    neighborhood,
+                    ..params(8, 8, topology)
+                };
+                let adjacency = Adjacency::new(&params);
+                let expected = neighbors(&params, 4, 4).len();
+
+                for (x, y) in (0..8).flat_map(|y| (0..8).map(move |x| (x, y))) {
+                    let around: HashSet<_> = adjacency.neighbors(x, y).collect();
+
+                    assert_eq!(around.len(), expected, "{neighborhood:?} at {x}, {y}");
+                    assert!(!around.contains(&(x, y)));
+                    for (xx, yy) in around {
+                        assert!(
+                            adjacency.neighbors(xx, yy).any(|pos| pos == (x, y)),
+                            "{neighborhood:?} from {x}, {y} to {xx}, {yy}"
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn presets_reach_as_far_as_named() {
+        let cases = [
+            (Topology::Square, [8, 4, 8, 24, 48], [3, 2, 2, 8, 15]),
+            (Topology::Hex, [6, 6, 12, 18, 36], [2, 2, 3, 6, 11]),
+        ];
+
+        for (topology, middle, corner) in cases {
+            for (neighborhood, (middle, corner)) in NEIGHBORHOOD_PRESETS
+                .into_iter()
+                .zip(middle.into_iter().zip(corner))
+            {
+                let params = Params {
+                    neighborhood,
+                    ..params(16, 16, topology)
+                };
+
+                assert_eq!(neighbors(&params, 8, 8).len(), middle, "{neighborhood:?}");
+                assert_eq!(neighbors(&params, 0, 0).len(), corner, "{neighborhood:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn hex_knight_turns_off_the_straight_line() {
+        let knight = |offset| Neighborhood::Knight.contains(Topology::Hex, offset);
+
+        assert!(knight((1, 2)));
+        assert!(knight((-3, 1)));
+        assert!(knight((2, -3)));
+
+        // straight lines, in each of the three directions
+        assert!(!knight((3, 0)));
+        assert!(!knight((0, -3)));
+        assert!(!knight((-3, 3)));
+
+        // too close
+        assert!(!knight((1, 1)));
+    }
+
+    #[test]
+    fn largest_zone_is_a_tile_and_its_neighbors() {
+        let cases = [
+            (Topology::Square, [9, 5, 9, 25, 49]),
+            (Topology::Hex, [7, 7, 13, 19, 37]),
+        ];
+
+        for (topology, zones) in cases {
+            for (neighborhood, zone) in NEIGHBORHOOD_PRESETS.into_iter().zip(zones) {
+                let params = Params {
+                    neighborhood,
+                    ..params(16, 16, topology)
+                };
+
+                assert_eq!(
+                    Adjacency::new(&params).largest_zone(),
+                    zone,
+                    "{neighborhood:?}"
+                );
+            }
+        }
+
+        // small boards cannot hold the whole zone
+        let small = params(2, 2, Topology::Square);
+        assert_eq!(Adjacency::new(&small).largest_zone(), 4);
+    }
+
+    #[test]
+    fn offsets_come_with_their_opposites() {
+        let offsets = Offsets::new([(1, 2), (-3, 0)]).unwrap();
+
+        assert_eq!(
+            offsets.iter().collect::<HashSet<_>>(),
+            HashSet::from([(1, 2), (-1, -2), (-3, 0), (3, 0)])
+        );
+        assert!(!offsets.contains((2, 1)));
+        assert!(!offsets.contains((MAX_REACH + 1, 0)));
+    }
+
+    #[test]
+    fn offsets_must_be_nonzero_and_in_reach() {
+        for offset in [(0, 0), (MAX_REACH + 1, 0), (0, -MAX_REACH - 1)] {
+            assert_eq!(
+                Offsets::new([(1, 0), offset]),
+                Err(ParamsError::BadNeighborhood)
+            );
+        }
+
+        assert!(Offsets::new([(MAX_REACH, -MAX_REACH)]).is_ok());
+    }
+}
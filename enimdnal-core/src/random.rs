@@ -0,0 +1,91 @@
+use std::fmt;
+use std::num::ParseIntError;
+use std::str::FromStr;
+
+use nanorand::{RandomRange, Rng, WyRand};
+use serde::{Deserialize, Serialize};
+
+/// Seed of the board generator.
+///
+/// The same seed, board parameters and first click
+/// always produce the same mine layout,
+/// which makes boards shareable and reproducible.
+///
+/// Seeds are displayed and parsed as 16 hexadecimal digits.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub struct Seed(u64);
+
+impl Seed {
+    pub fn random() -> Self {
+        Self(WyRand::new().generate())
+    }
+
+    /// A fresh generator that always yields the same sequence for this seed.
+    pub fn rng(self) -> WyRand {
+        WyRand::new_seed(self.0)
+    }
+}
+
+impl From<u64> for Seed {
+    fn from(value: u64) -> Self {
+        Self(value)
+    }
+}
+
+impl From<Seed> for String {
+    fn from(value: Seed) -> Self {
+        value.to_string()
+    }
+}
+
+impl TryFrom<String> for Seed {
+    type Error = ParseIntError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl fmt::Display for Seed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:016X}", self.0)
+    }
+}
+
+impl FromStr for Seed {
+    type Err = ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        u64::from_str_radix(s.trim(), 16).map(Self)
+    }
+}
+
+pub trait IteratorRandom: Iterator + Sized {
+    fn choose_multiple<const OUT: usize, R: Rng<OUT>>(
+        mut self,
+        rng: &mut R,
+        n: usize,
+    ) -> Vec<Self::Item> {
+        let mut reservoir = Vec::with_capacity(n);
+        reservoir.extend(self.by_ref().take(n));
+
+        if reservoir.len() < n {
+            reservoir.shrink_to_fit();
+            return reservoir;
+        }
+
+        for (i, elem) in self.enumerate() {
+            let upper_bound = n + i + 1;
+            let index = RandomRange::random_range(rng, 0..upper_bound);
+
+            if let Some(slot) = reservoir.get_mut(index) {
+                *slot = elem;
+            }
+        }
+
+        reservoir
+    }
+}
+
+impl<I: Iterator + Sized> IteratorRandom for I {}
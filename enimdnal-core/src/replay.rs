@@ -0,0 +1,145 @@
+use std::fs;
+use std::io;
+use std::iter;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::minefield::{Board, MarkCycle, Params};
+use crate::random::Seed;
+
+/// Version of the replay file format, bumped on every incompatible change.
+const VERSION: u32 = 1;
+
+/// An input that reached the [Board].
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub enum Action {
+    Primary(usize, usize),
+    Secondary(usize, usize),
+    Chord(usize, usize),
+    Undo,
+    Redo,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Event {
+    /// Run timer reading at the moment of the input.
+    pub time_milisec: u32,
+    pub action: Action,
+}
+
+/// Complete record of a single game.
+///
+/// Playback regenerates the board from the seed,
+/// the mine layout is kept for reference and to detect generator changes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Replay {
+    version: u32,
+    params: Params,
+    seed: Seed,
+    mines: Vec<(usize, usize)>,
+    duration_milisec: u32,
+    events: Vec<Event>,
+
+    /// Order of marks, which changes the outcome of the recorded secondary actions.
+    #[serde(default)]
+    mark_cycle: MarkCycle,
+
+    /// Hints asked for during the game, which are not among the recorded inputs.
+    #[serde(default)]
+    hints_used: u32,
+}
+
+impl Replay {
+    /// Wrap up the `events` of a finished game played on `board`.
+    pub fn new(board: &Board, events: Vec<Event>, duration_milisec: u32, hints_used: u32) -> Self {
+        Self {
+            version: VERSION,
+            params: board.params(),
+            seed: board.seed(),
+            mines: mine_positions(board),
+            duration_milisec,
+            events,
+            mark_cycle: board.mark_cycle(),
+            hints_used,
+        }
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let contents = fs::read_to_string(path)?;
+        let replay: Self = serde_json::from_str(&contents)?;
+
+        if replay.version != VERSION {
+            let message = format!("unsupported replay version {}", replay.version);
+            return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+        }
+
+        Ok(replay)
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let contents = serde_json::to_string(self)?;
+        fs::write(path, contents)
+    }
+
+    pub fn params(&self) -> Params {
+        self.params
+    }
+
+    pub fn seed(&self) -> Seed {
+        self.seed
+    }
+
+    /// A fresh board for the events to be played back on.
+    pub fn board(&self) -> Board {
+        let mut board = Board::with_seed(self.params, self.seed);
+        board.set_mark_cycle(self.mark_cycle);
+        board
+    }
+
+    /// Whether the played back `board` ended up with the recorded mine layout.
+    pub fn matches_layout(&self, board: &Board) -> bool {
+        mine_positions(board) == self.mines
+    }
+
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
+    pub fn duration_milisec(&self) -> u32 {
+        self.duration_milisec
+    }
+
+    pub fn hints_used(&self) -> u32 {
+        self.hints_used
+    }
+}
+
+/// Feed a recorded input to the board.
+///
+/// Returns `false` for an undo or redo with nothing to act on.
+pub fn apply(board: &mut Board, action: Action) -> bool {
+    match action {
+        Action::Primary(x, y) => board.handle_primary_action(x, y),
+        Action::Secondary(x, y) => board.handle_secondary_action(x, y),
+        Action::Chord(x, y) => board.handle_chord_action(x, y),
+        Action::Undo => return board.undo(),
+        Action::Redo => return board.redo(),
+    }
+
+    true
+}
+
+/// Positions of the mines, repeated for every mine of a stack.
+fn mine_positions(board: &Board) -> Vec<(usize, usize)> {
+    let (width, height) = board.dims();
+
+    (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .flat_map(|(x, y)| iter::repeat_n((x, y), board.tile(x, y).mines() as usize))
+        .collect()
+}
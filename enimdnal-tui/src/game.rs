@@ -0,0 +1,129 @@
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, MouseButton, MouseEventKind};
+
+use enimdnal_core::clock::GameClock;
+use enimdnal_core::minefield::{Board, MarkCycle, Params};
+use enimdnal_core::random::Seed;
+
+use crate::render;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Stage {
+    Playing,
+    Victory,
+    Defeat,
+}
+
+#[derive(Debug)]
+pub struct Game {
+    pub board: Board,
+    pub stage: Stage,
+    pub cursor: (usize, usize),
+    pub quit: bool,
+
+    /// Time of the run, which only runs between the first click and the end of the game.
+    pub clock: GameClock,
+}
+
+impl Game {
+    pub fn new(params: Params, seed: Seed, marks: MarkCycle) -> Self {
+        let (width, height) = (params.width(), params.height());
+        let mut board = Board::with_seed(params, seed);
+        board.set_mark_cycle(marks);
+
+        Self {
+            board,
+            stage: Stage::Playing,
+            cursor: (width / 2, height / 2),
+            quit: false,
+            clock: GameClock::new(),
+        }
+    }
+
+    pub fn handle(&mut self, event: Event) {
+        match event {
+            Event::Key(key) if key.kind != KeyEventKind::Release => self.handle_key(key),
+            Event::Mouse(mouse) => {
+                let Some(pos) = render::screen_to_board(&self.board, mouse.column, mouse.row)
+                else {
+                    return;
+                };
+
+                match mouse.kind {
+                    MouseEventKind::Down(MouseButton::Left) => {
+                        self.cursor = pos;
+                        self.primary();
+                    }
+                    MouseEventKind::Down(MouseButton::Right) => {
+                        self.cursor = pos;
+                        self.secondary();
+                    }
+                    MouseEventKind::Moved => self.cursor = pos,
+                    _ => (),
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Char('n') => self.restart(Seed::random()),
+            KeyCode::Char('r') => self.restart(self.board.seed()),
+            KeyCode::Up | KeyCode::Char('k') | KeyCode::Char('w') => self.move_cursor(0, -1),
+            KeyCode::Down | KeyCode::Char('j') | KeyCode::Char('s') => self.move_cursor(0, 1),
+            KeyCode::Left | KeyCode::Char('h') | KeyCode::Char('a') => self.move_cursor(-1, 0),
+            KeyCode::Right | KeyCode::Char('l') | KeyCode::Char('d') => self.move_cursor(1, 0),
+            KeyCode::Char(' ') | KeyCode::Enter => self.primary(),
+            KeyCode::Char('f') => self.secondary(),
+            _ => (),
+        }
+    }
+
+    fn move_cursor(&mut self, dx: isize, dy: isize) {
+        let (width, height) = self.board.dims();
+        let (x, y) = self.cursor;
+
+        self.cursor = (
+            x.saturating_add_signed(dx).min(width - 1),
+            y.saturating_add_signed(dy).min(height - 1),
+        );
+    }
+
+    fn primary(&mut self) {
+        if self.stage != Stage::Playing {
+            return;
+        }
+
+        let (x, y) = self.cursor;
+        self.board.handle_primary_action(x, y);
+
+        if self.board.is_initialized() {
+            self.clock.start();
+        }
+
+        if self.board.is_defeat() {
+            self.clock.pause();
+            self.stage = Stage::Defeat;
+        } else if self.board.is_victory() {
+            self.clock.pause();
+            self.stage = Stage::Victory;
+        }
+    }
+
+    fn secondary(&mut self) {
+        if self.stage != Stage::Playing {
+            return;
+        }
+
+        let (x, y) = self.cursor;
+        self.board.handle_secondary_action(x, y);
+    }
+
+    /// Start over on a board generated from `seed`.
+    fn restart(&mut self, seed: Seed) {
+        self.board.reset(seed);
+        self.stage = Stage::Playing;
+        self.clock.reset();
+    }
+}
@@ -0,0 +1,141 @@
+mod game;
+mod render;
+
+use std::io::{self, Write};
+use std::time::Duration;
+
+use crossterm::event::{self, DisableMouseCapture, EnableMouseCapture};
+use crossterm::terminal::{self, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{cursor, execute};
+
+use enimdnal_core::minefield::{self, Generation, MarkCycle, Params};
+use enimdnal_core::random::Seed;
+
+use game::Game;
+
+/// How long to wait for input before redrawing the running timer.
+const TICK: Duration = Duration::from_millis(50);
+
+#[derive(Debug)]
+struct Args {
+    seed: Option<Seed>,
+    no_guess: bool,
+    difficulty: Params,
+    marks: MarkCycle,
+}
+
+impl Default for Args {
+    fn default() -> Self {
+        Self {
+            seed: None,
+            no_guess: false,
+            difficulty: minefield::EXPERT,
+            marks: MarkCycle::default(),
+        }
+    }
+}
+
+/// Puts the terminal into raw mode for the lifetime of the value,
+/// restoring it even when the game panics.
+struct Terminal;
+
+impl Terminal {
+    fn enter() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        execute!(
+            io::stdout(),
+            EnterAlternateScreen,
+            EnableMouseCapture,
+            cursor::Hide,
+            terminal::Clear(terminal::ClearType::All)
+        )?;
+
+        Ok(Self)
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        let _ = execute!(
+            io::stdout(),
+            cursor::Show,
+            DisableMouseCapture,
+            LeaveAlternateScreen
+        );
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+fn main() -> Result<(), String> {
+    let args = parse_args()?;
+    let generation = if args.no_guess {
+        Generation::NoGuess
+    } else {
+        Generation::Random
+    };
+    let params = args.difficulty.with_generation(generation);
+    let seed = args.seed.unwrap_or_else(Seed::random);
+
+    run(Game::new(params, seed, args.marks)).map_err(|e| format!("Terminal error: {e}"))
+}
+
+fn run(mut game: Game) -> io::Result<()> {
+    let _terminal = Terminal::enter()?;
+    let mut stdout = io::stdout();
+
+    while !game.quit {
+        render::draw(&mut stdout, &game)?;
+        stdout.flush()?;
+
+        if event::poll(TICK)? {
+            game.handle(event::read()?);
+        }
+    }
+
+    Ok(())
+}
+
+/// Supported arguments:
+///
+/// - `--seed <HEX>` to play a specific, shared board
+/// - `--no-guess` to only generate boards solvable without guessing
+/// - `--difficulty <beginner|intermediate|expert>` to pick the board size
+/// - `--marks <flag|flag-unsure|unsure-flag>` to pick the order of marks
+fn parse_args() -> Result<Args, String> {
+    let mut parsed = Args::default();
+    let mut args = std::env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--seed" => {
+                let value = args.next().ok_or("Missing value for --seed")?;
+                let seed = value
+                    .parse()
+                    .map_err(|e| format!("Invalid seed {value:?}: {e}"))?;
+                parsed.seed = Some(seed);
+            }
+            "--no-guess" => parsed.no_guess = true,
+            "--difficulty" => {
+                let value = args.next().ok_or("Missing value for --difficulty")?;
+                parsed.difficulty = match value.as_str() {
+                    "beginner" => minefield::BEGINNER,
+                    "intermediate" => minefield::INTERMEDIATE,
+                    "expert" => minefield::EXPERT,
+                    _ => return Err(format!("Unknown difficulty {value:?}")),
+                };
+            }
+            "--marks" => {
+                let value = args.next().ok_or("Missing value for --marks")?;
+                parsed.marks = match value.as_str() {
+                    "flag" => MarkCycle::FlagOnly,
+                    "flag-unsure" => MarkCycle::FlagUnsure,
+                    "unsure-flag" => MarkCycle::UnsureFirst,
+                    _ => return Err(format!("Unknown order of marks {value:?}")),
+                };
+            }
+            _ => return Err(format!("Unknown argument {arg:?}")),
+        }
+    }
+
+    Ok(parsed)
+}
@@ -0,0 +1,157 @@
+use std::io::{self, Write};
+
+use crossterm::style::{Attribute, Color, Print, SetAttribute, SetForegroundColor, Stylize};
+use crossterm::terminal::{Clear, ClearType};
+use crossterm::{cursor, queue};
+
+use enimdnal_core::minefield::{Board, Cover, Mark, Object, Tile};
+
+use crate::game::{Game, Stage};
+
+/// Terminal row of the top board edge, below the status line.
+const BOARD_TOP: u16 = 2;
+const BOARD_LEFT: u16 = 2;
+
+/// Terminal columns taken by a single tile, so that the board comes out roughly square.
+const TILE_WIDTH: u16 = 2;
+
+const COVER_COLOR: Color = Color::DarkGrey;
+const FLAG_COLOR: Color = Color::Red;
+const UNSURE_COLOR: Color = Color::Blue;
+const MINE_COLOR: Color = Color::White;
+const WIN_COLOR: Color = Color::Green;
+
+/// Classic minesweeper colors of hints from 1 to 8.
+const HINT_COLORS: [Color; 8] = [
+    Color::Blue,
+    Color::Green,
+    Color::Red,
+    Color::DarkBlue,
+    Color::DarkRed,
+    Color::Cyan,
+    Color::Magenta,
+    Color::Grey,
+];
+
+/// Draw over the previous frame, the layout never moves for a given board.
+pub fn draw(out: &mut impl Write, game: &Game) -> io::Result<()> {
+    draw_status(out, game)?;
+
+    let (width, height) = game.board.dims();
+    for y in 0..height {
+        queue!(out, cursor::MoveTo(BOARD_LEFT, BOARD_TOP + y as u16))?;
+
+        for x in 0..width {
+            draw_tile(out, game, x, y)?;
+        }
+    }
+
+    draw_footer(out, game, BOARD_TOP + height as u16 + 1)
+}
+
+/// Board tile under the given terminal cell.
+pub fn screen_to_board(board: &Board, column: u16, row: u16) -> Option<(usize, usize)> {
+    let (width, height) = board.dims();
+
+    let x = column.checked_sub(BOARD_LEFT)? / TILE_WIDTH;
+    let y = row.checked_sub(BOARD_TOP)?;
+
+    let (x, y) = (x as usize, y as usize);
+    (x < width && y < height).then_some((x, y))
+}
+
+fn draw_status(out: &mut impl Write, game: &Game) -> io::Result<()> {
+    let elapsed = game.clock.elapsed_milisec();
+
+    let milis = elapsed % 1000;
+    let secs = (elapsed / 1000) % 60;
+    let mins = elapsed / 60_000;
+
+    let mut status = format!(
+        "{:02}:{:02}.{:03}   {:03} / {:03}   seed {}",
+        mins,
+        secs,
+        milis,
+        game.board.flags(),
+        game.board.mines(),
+        game.board.seed()
+    );
+    if game.board.is_no_guess_fallback() {
+        status.push_str("   (may need guessing)");
+    }
+
+    queue!(
+        out,
+        cursor::MoveTo(BOARD_LEFT, 0),
+        Clear(ClearType::CurrentLine),
+        Print(status)
+    )
+}
+
+fn draw_tile(out: &mut impl Write, game: &Game, x: usize, y: usize) -> io::Result<()> {
+    let tile = game.board.tile(x, y);
+    let (glyph, color) = glyph(game, tile);
+
+    let mut styled = format!("{glyph} ").with(color);
+    if (x, y) == game.cursor {
+        styled = styled.attribute(Attribute::Reverse);
+    }
+
+    queue!(out, Print(styled), SetAttribute(Attribute::Reset))
+}
+
+fn glyph(game: &Game, tile: Tile) -> (char, Color) {
+    let game_over = game.stage != Stage::Playing;
+
+    match (tile.cover(), tile.object()) {
+        // the mines are revealed once the game is over
+        (Cover::Up(_), Object::Mine(mines)) if game_over => match game.stage {
+            Stage::Victory => (stacked('*', mines), WIN_COLOR),
+            _ => (stacked('*', mines), MINE_COLOR),
+        },
+        (Cover::Up(Mark::Flag(flags)), _) => (stacked('F', flags), FLAG_COLOR),
+        (Cover::Up(Mark::Unsure), _) => ('?', UNSURE_COLOR),
+        (Cover::Up(Mark::None), _) => ('#', COVER_COLOR),
+        (Cover::Down, Object::Mine(mines)) => (stacked('*', mines), FLAG_COLOR),
+        (Cover::Down, Object::Hint(n)) => {
+            let digit = char::from_digit(n as u32, 10).unwrap_or('?');
+            (digit, HINT_COLORS[(n as usize - 1) % HINT_COLORS.len()])
+        }
+        (Cover::Down, Object::Blank) => ('.', COVER_COLOR),
+    }
+}
+
+/// The glyph of a single mine or flag, or the size of a stack of them.
+fn stacked(single: char, count: u8) -> char {
+    match count {
+        1 => single,
+        _ => char::from_digit(count as u32, 10).unwrap_or(single),
+    }
+}
+
+fn draw_footer(out: &mut impl Write, game: &Game, row: u16) -> io::Result<()> {
+    let outcome = match game.stage {
+        Stage::Playing => None,
+        Stage::Victory => Some("YOU WIN".with(WIN_COLOR)),
+        Stage::Defeat => Some("BOOM".with(FLAG_COLOR)),
+    };
+
+    queue!(
+        out,
+        cursor::MoveTo(BOARD_LEFT, row),
+        Clear(ClearType::CurrentLine)
+    )?;
+    if let Some(outcome) = outcome {
+        queue!(out, Print(outcome.bold()))?;
+    }
+
+    queue!(
+        out,
+        SetForegroundColor(Color::Grey),
+        cursor::MoveTo(BOARD_LEFT, row + 2),
+        Print("[arrows/hjkl/wasd] move  [space] uncover  [f] flag"),
+        cursor::MoveTo(BOARD_LEFT, row + 3),
+        Print("[n] new game  [r] retry board  [q] quit"),
+        SetAttribute(Attribute::Reset)
+    )
+}
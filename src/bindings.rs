@@ -0,0 +1,327 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use notan::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Something the player can do with a key or mouse button of their choice.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Serialize, Deserialize)]
+pub enum Command {
+    Uncover,
+    Flag,
+    Chord,
+    Pause,
+    Restart,
+    Hint,
+    Undo,
+    Redo,
+
+    /// Drag the board around while held.
+    Pan,
+}
+
+pub const COMMANDS: [Command; 9] = [
+    Command::Uncover,
+    Command::Flag,
+    Command::Chord,
+    Command::Pause,
+    Command::Restart,
+    Command::Hint,
+    Command::Undo,
+    Command::Redo,
+    Command::Pan,
+];
+
+/// Input that sets off a [Command].
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub enum Trigger {
+    Key(KeyCode),
+
+    /// A key pressed while holding Ctrl.
+    Ctrl(KeyCode),
+    Mouse(MouseButton),
+
+    /// Left and right mouse buttons pressed together.
+    LeftRight,
+}
+
+/// Where a command came from, which decides the tile it applies to:
+/// the one under the mouse or the one under the keyboard cursor.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Device {
+    Keyboard,
+    Mouse,
+}
+
+/// Part of the game in which inputs are listened to.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Context {
+    Playing,
+    Finished,
+}
+
+/// Keys with a fixed meaning, that no command can be bound to.
+///
+/// Modifiers are not taken into account, so e.g. both S and Ctrl+S are taken.
+const RESERVED: [(KeyCode, &str, &[Context]); 18] = [
+    (
+        KeyCode::C,
+        "copying the seed",
+        &[Context::Playing, Context::Finished],
+    ),
+    (
+        KeyCode::Escape,
+        "the menu",
+        &[Context::Playing, Context::Finished],
+    ),
+    (KeyCode::P, "mine probabilities", &[Context::Playing]),
+    (KeyCode::F2, "practice mode", &[Context::Playing]),
+    (KeyCode::Back, "retrying the board", &[Context::Finished]),
+    (KeyCode::R, "watching the replay", &[Context::Finished]),
+    (KeyCode::Up, "moving the cursor", &[Context::Playing]),
+    (KeyCode::Down, "moving the cursor", &[Context::Playing]),
+    (KeyCode::Left, "moving the cursor", &[Context::Playing]),
+    (KeyCode::Right, "moving the cursor", &[Context::Playing]),
+    (KeyCode::W, "moving the cursor", &[Context::Playing]),
+    (KeyCode::A, "moving the cursor", &[Context::Playing]),
+    (
+        KeyCode::S,
+        "moving the cursor and saving",
+        &[Context::Playing],
+    ),
+    (KeyCode::D, "moving the cursor", &[Context::Playing]),
+    (KeyCode::H, "moving the cursor", &[Context::Playing]),
+    (KeyCode::J, "moving the cursor", &[Context::Playing]),
+    (KeyCode::K, "moving the cursor", &[Context::Playing]),
+    (KeyCode::L, "moving the cursor", &[Context::Playing]),
+];
+
+/// Keys and mouse buttons assigned to every [Command].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bindings(BTreeMap<Command, Vec<Trigger>>);
+
+impl Command {
+    fn contexts(self) -> &'static [Context] {
+        match self {
+            Command::Restart => &[Context::Finished],
+            Command::Undo | Command::Pan => &[Context::Playing, Context::Finished],
+            _ => &[Context::Playing],
+        }
+    }
+
+    fn default_triggers(self) -> Vec<Trigger> {
+        match self {
+            Command::Uncover => vec![
+                Trigger::Mouse(MouseButton::Left),
+                Trigger::Key(KeyCode::Space),
+            ],
+            Command::Flag => vec![Trigger::Mouse(MouseButton::Right), Trigger::Key(KeyCode::F)],
+            Command::Chord => vec![Trigger::Key(KeyCode::E), Trigger::LeftRight],
+            Command::Pause => vec![Trigger::Key(KeyCode::Return)],
+            Command::Restart => vec![Trigger::Key(KeyCode::Space)],
+            Command::Hint => vec![Trigger::Key(KeyCode::T)],
+            Command::Undo => vec![Trigger::Ctrl(KeyCode::Z)],
+            Command::Redo => vec![Trigger::Ctrl(KeyCode::Y)],
+            Command::Pan => vec![Trigger::Mouse(MouseButton::Middle)],
+        }
+    }
+}
+
+impl Trigger {
+    fn device(self) -> Device {
+        match self {
+            Trigger::Key(_) | Trigger::Ctrl(_) => Device::Keyboard,
+            Trigger::Mouse(_) | Trigger::LeftRight => Device::Mouse,
+        }
+    }
+
+    /// Whether the trigger went off this frame.
+    ///
+    /// With `left_right` set, a button pressed while the other one is held
+    /// only counts as [Trigger::LeftRight].
+    fn fired(self, app: &App, left_right: bool) -> bool {
+        let keyboard = &app.keyboard;
+
+        match self {
+            Trigger::Key(key) => !keyboard.ctrl() && keyboard.was_pressed(key),
+            Trigger::Ctrl(key) => keyboard.ctrl() && keyboard.was_pressed(key),
+            Trigger::Mouse(button) => {
+                let together = matches!(button, MouseButton::Left | MouseButton::Right)
+                    && both_buttons_pressed(app);
+                app.mouse.was_pressed(button) && !(left_right && together)
+            }
+            Trigger::LeftRight => both_buttons_pressed(app),
+        }
+    }
+
+    fn is_down(self, app: &App) -> bool {
+        let keyboard = &app.keyboard;
+
+        match self {
+            Trigger::Key(key) => !keyboard.ctrl() && keyboard.is_down(key),
+            Trigger::Ctrl(key) => keyboard.ctrl() && keyboard.is_down(key),
+            Trigger::Mouse(button) => app.mouse.is_down(button),
+            Trigger::LeftRight => app.mouse.left_is_down() && app.mouse.right_is_down(),
+        }
+    }
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        let bindings = COMMANDS
+            .iter()
+            .map(|&command| (command, command.default_triggers()))
+            .collect();
+
+        Self(bindings)
+    }
+}
+
+impl Bindings {
+    /// Read bindings from `path`, commands missing from the file keep their defaults.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let contents = fs::read_to_string(path)?;
+        let mut bindings: BTreeMap<Command, Vec<Trigger>> = serde_json::from_str(&contents)?;
+
+        for command in COMMANDS {
+            bindings
+                .entry(command)
+                .or_insert_with(|| command.default_triggers());
+        }
+
+        Ok(Self(bindings))
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let contents = serde_json::to_string_pretty(&self.0)?;
+        fs::write(path, contents)
+    }
+
+    /// The device that set off `command` this frame, if any.
+    pub fn triggered_by(&self, command: Command, app: &App) -> Option<Device> {
+        let left_right = self.0.values().flatten().any(|&t| t == Trigger::LeftRight);
+
+        self.triggers(command)
+            .iter()
+            .find(|trigger| trigger.fired(app, left_right))
+            .map(|trigger| trigger.device())
+    }
+
+    pub fn triggered(&self, command: Command, app: &App) -> bool {
+        self.triggered_by(command, app).is_some()
+    }
+
+    /// Whether `command` is held down at the moment, for commands that last.
+    pub fn held(&self, command: Command, app: &App) -> bool {
+        self.triggers(command)
+            .iter()
+            .any(|trigger| trigger.is_down(app))
+    }
+
+    pub fn triggers(&self, command: Command) -> &[Trigger] {
+        self.0.get(&command).map_or(&[], Vec::as_slice)
+    }
+
+    /// Short description of the main trigger of `command`, for on-screen help.
+    ///
+    /// Keys are preferred, since the mouse buttons mostly go without saying.
+    pub fn label(&self, command: Command) -> String {
+        let triggers = self.triggers(command);
+        let key = triggers.iter().find(|t| t.device() == Device::Keyboard);
+
+        match key.or(triggers.first()) {
+            Some(trigger) => trigger.to_string(),
+            None => "unbound".to_string(),
+        }
+    }
+
+    /// Every trigger of `command`, for listing on the controls screen.
+    pub fn describe(&self, command: Command) -> String {
+        let triggers: Vec<_> = self
+            .triggers(command)
+            .iter()
+            .map(|t| t.to_string())
+            .collect();
+
+        match triggers.is_empty() {
+            true => "unbound".to_string(),
+            false => triggers.join(", "),
+        }
+    }
+
+    /// Replace the triggers of `command` with a single one.
+    pub fn set(&mut self, command: Command, trigger: Trigger) {
+        self.0.insert(command, vec![trigger]);
+    }
+
+    pub fn reset(&mut self, command: Command) {
+        self.0.insert(command, command.default_triggers());
+    }
+
+    /// Why a trigger of `command` clashes with another input, if it does.
+    pub fn conflict(&self, command: Command) -> Option<String> {
+        let shares_context = |other: Command| {
+            other
+                .contexts()
+                .iter()
+                .any(|c| command.contexts().contains(c))
+        };
+
+        for &trigger in self.triggers(command) {
+            let taken_by = COMMANDS.iter().find(|&&other| {
+                other != command && shares_context(other) && self.triggers(other).contains(&trigger)
+            });
+            if let Some(other) = taken_by {
+                return Some(format!("{trigger} is also bound to {other:?}"));
+            }
+
+            let (Trigger::Key(key) | Trigger::Ctrl(key)) = trigger else {
+                continue;
+            };
+            let reserved = RESERVED.iter().find(|(reserved, _, contexts)| {
+                *reserved == key && contexts.iter().any(|c| command.contexts().contains(c))
+            });
+            if let Some((_, purpose, _)) = reserved {
+                return Some(format!("{trigger} is reserved for {purpose}"));
+            }
+        }
+
+        None
+    }
+
+    pub fn has_conflicts(&self) -> bool {
+        COMMANDS
+            .iter()
+            .any(|&command| self.conflict(command).is_some())
+    }
+}
+
+impl fmt::Display for Trigger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Trigger::Key(key) => write!(f, "{key:?}"),
+            Trigger::Ctrl(key) => write!(f, "Ctrl+{key:?}"),
+            Trigger::Mouse(button) => write!(f, "{button:?} click"),
+            Trigger::LeftRight => write!(f, "Left+Right click"),
+        }
+    }
+}
+
+/// Location of the bindings file, inside the platform's config directory.
+pub fn bindings_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("enimdnal").join("bindings.json"))
+}
+
+fn both_buttons_pressed(app: &App) -> bool {
+    let mouse = &app.mouse;
+
+    (mouse.left_was_pressed() && mouse.right_is_down())
+        || (mouse.right_was_pressed() && mouse.left_is_down())
+}
@@ -0,0 +1,338 @@
+use std::ops::Range;
+
+use enimdnal_core::minefield::Topology;
+
+/// Closest and furthest the board can be zoomed, relative to fitting the window.
+const ZOOM_RANGE: (f32, f32) = (1., 8.);
+
+/// Zoom change per step of the mouse wheel.
+const ZOOM_STEP: f32 = 1.25;
+
+/// Height of a hex tile, relative to its width.
+const HEX_HEIGHT: f32 = 1.154_700_5;
+
+/// Distance between the rows of hex tiles, relative to their width,
+/// as the tiles of neighboring rows fit into each other.
+const HEX_ROW_PITCH: f32 = 0.866_025_4;
+
+/// Zoom and pan of the board, on top of fitting it into the window.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Camera {
+    zoom: f32,
+
+    /// Shift of the board from the middle of the viewport, in pixels.
+    pan: (f32, f32),
+}
+
+/// Where the board goes in the window.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Frame {
+    /// Size of the window area left for the board.
+    pub viewport: (f32, f32),
+
+    /// Board size, in tiles.
+    pub dims: (usize, usize),
+    pub topology: Topology,
+
+    /// Whether the board wraps around, and is drawn with a ring of ghost tiles
+    /// showing the tiles across the opposite edge.
+    pub wrapping: bool,
+
+    /// Tile size the board is drawn with when it fits the viewport.
+    pub max_tile_size: f32,
+}
+
+/// Board placement on the screen, as seen through the [Camera].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct View {
+    /// Screen position of the top left corner of the board.
+    pub origin: (f32, f32),
+
+    /// Width of a tile, which for square tiles is also their height.
+    pub tile_size: f32,
+    pub topology: Topology,
+    pub wrapping: bool,
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Self {
+            zoom: ZOOM_RANGE.0,
+            pan: (0., 0.),
+        }
+    }
+}
+
+impl Camera {
+    pub fn view(&self, frame: Frame) -> View {
+        let tile_size = fit_tile_size(frame) * self.zoom;
+        let (centered_x, centered_y) = centered_origin(frame, tile_size);
+        let (pan_x, pan_y) = self.pan;
+
+        View {
+            origin: (centered_x + pan_x, centered_y + pan_y),
+            tile_size,
+            topology: frame.topology,
+            wrapping: frame.wrapping,
+        }
+    }
+
+    /// Move the board by `delta` pixels, as far as it stays covering the viewport.
+    pub fn pan_by(&mut self, frame: Frame, (dx, dy): (f32, f32)) {
+        self.pan = (self.pan.0 + dx, self.pan.1 + dy);
+        self.clamp(frame);
+    }
+
+    /// Zoom in or out by `steps` of the mouse wheel,
+    /// keeping the board point under `anchor` in place.
+    pub fn zoom_at(&mut self, frame: Frame, anchor: (f32, f32), steps: f32) {
+        let before = self.view(frame);
+        let (anchor_x, anchor_y) = anchor;
+        let board_x = (anchor_x - before.origin.0) / before.tile_size;
+        let board_y = (anchor_y - before.origin.1) / before.tile_size;
+
+        let (min_zoom, max_zoom) = ZOOM_RANGE;
+        self.zoom = (self.zoom * ZOOM_STEP.powf(steps)).clamp(min_zoom, max_zoom);
+
+        let tile_size = fit_tile_size(frame) * self.zoom;
+        let (centered_x, centered_y) = centered_origin(frame, tile_size);
+        self.pan = (
+            anchor_x - board_x * tile_size - centered_x,
+            anchor_y - board_y * tile_size - centered_y,
+        );
+        self.clamp(frame);
+    }
+
+    /// Keep the board from being panned off the viewport,
+    /// e.g. after the window has been resized.
+    pub fn clamp(&mut self, frame: Frame) {
+        let tile_size = fit_tile_size(frame) * self.zoom;
+        let (viewport_width, viewport_height) = frame.viewport;
+        let (board_width, board_height) = frame_extent(frame);
+
+        // a board smaller than the viewport stays in the middle of it
+        let limit = |board: f32, viewport: f32| f32::max(0., (board - viewport) / 2.);
+        let limit_x = limit(board_width * tile_size, viewport_width);
+        let limit_y = limit(board_height * tile_size, viewport_height);
+
+        self.pan = (
+            self.pan.0.clamp(-limit_x, limit_x),
+            self.pan.1.clamp(-limit_y, limit_y),
+        );
+    }
+}
+
+impl View {
+    /// Screen position of the top left corner of the box around a tile,
+    /// also for the positions just outside the board, where the ghost tiles go.
+    fn grid_to_screen(&self, x: isize, y: isize) -> (f32, f32) {
+        let (origin_x, origin_y) = self.origin;
+
+        match self.topology {
+            Topology::Square => (
+                origin_x + x as f32 * self.tile_size,
+                origin_y + y as f32 * self.tile_size,
+            ),
+            Topology::Hex => (
+                origin_x + (x as f32 + hex_row_shift(y)) * self.tile_size,
+                origin_y + y as f32 * HEX_ROW_PITCH * self.tile_size,
+            ),
+        }
+    }
+
+    /// Size of the box around a tile.
+    pub fn tile_dims(&self) -> (f32, f32) {
+        match self.topology {
+            Topology::Square => (self.tile_size, self.tile_size),
+            Topology::Hex => (self.tile_size, self.tile_size * HEX_HEIGHT),
+        }
+    }
+
+    pub fn tile_center(&self, x: usize, y: usize) -> (f32, f32) {
+        self.grid_center(x as isize, y as isize)
+    }
+
+    fn grid_center(&self, x: isize, y: isize) -> (f32, f32) {
+        let (screen_x, screen_y) = self.grid_to_screen(x, y);
+        let (width, height) = self.tile_dims();
+
+        (screen_x + width / 2., screen_y + height / 2.)
+    }
+
+    /// Ghost tiles around a wrapping board of `dims` tiles,
+    /// as their screen centers along with the tiles across the edge they repeat.
+    pub fn ghosts(&self, (cols, rows): (usize, usize)) -> Vec<((f32, f32), (usize, usize))> {
+        if !self.wrapping {
+            return vec![];
+        }
+
+        let (cols, rows) = (cols as isize, rows as isize);
+        let ring = (-1..=rows).flat_map(|y| {
+            (-1..=cols)
+                .filter(move |&x| x < 0 || x == cols || y < 0 || y == rows)
+                .map(move |x| (x, y))
+        });
+
+        ring.map(|(x, y)| {
+            let tile = (x.rem_euclid(cols) as usize, y.rem_euclid(rows) as usize);
+            (self.grid_center(x, y), tile)
+        })
+        .collect()
+    }
+
+    /// Columns and rows of the board at least partly inside the viewport,
+    /// so that huge boards only draw what can be seen.
+    pub fn visible(&self, frame: Frame) -> (Range<usize>, Range<usize>) {
+        let (origin_x, origin_y) = self.origin;
+        let (viewport_width, viewport_height) = frame.viewport;
+        let (cols, rows) = frame.dims;
+
+        // tiles are `stride` apart, each reaching `span` past its own start
+        let range = |origin: f32, viewport: f32, stride: f32, span: f32, len: usize| {
+            let first = f32::max(((-origin - span) / stride).floor() + 1., 0.) as usize;
+            let last = f32::max((viewport - origin) / stride, 0.).ceil() as usize;
+            first.min(len)..last.min(len)
+        };
+
+        let (col_span, row_stride) = match self.topology {
+            Topology::Square => (1., 1.),
+            Topology::Hex => (1.5, HEX_ROW_PITCH),
+        };
+        let (_, tile_height) = self.tile_dims();
+
+        (
+            range(
+                origin_x,
+                viewport_width,
+                self.tile_size,
+                col_span * self.tile_size,
+                cols,
+            ),
+            range(
+                origin_y,
+                viewport_height,
+                row_stride * self.tile_size,
+                tile_height,
+                rows,
+            ),
+        )
+    }
+
+    /// Tile of a `dims` sized board at a screen position, if there is one.
+    pub fn screen_to_board(
+        &self,
+        (dims_x, dims_y): (usize, usize),
+        (screen_x, screen_y): (f32, f32),
+    ) -> Option<(usize, usize)> {
+        let (origin_x, origin_y) = self.origin;
+        let (board_x, board_y) = match self.topology {
+            Topology::Square => (
+                f32::floor((screen_x - origin_x) / self.tile_size),
+                f32::floor((screen_y - origin_y) / self.tile_size),
+            ),
+            Topology::Hex => self.hex_at(screen_x - origin_x, screen_y - origin_y),
+        };
+
+        // ghost tiles stand for the tiles across the edge
+        if self.wrapping {
+            let wrap = |pos: f32, len: usize| {
+                let len = len as f32;
+                (-1. ..=len)
+                    .contains(&pos)
+                    .then(|| pos.rem_euclid(len) as usize)
+            };
+            return Some((wrap(board_x, dims_x)?, wrap(board_y, dims_y)?));
+        }
+
+        if board_x < 0. || board_y < 0. {
+            return None;
+        }
+
+        let (board_x, board_y) = (board_x as usize, board_y as usize);
+        (board_x < dims_x && board_y < dims_y).then_some((board_x, board_y))
+    }
+
+    /// Column and row of the hex containing a point relative to the board origin,
+    /// which is the one with the nearest center, as hexes tile the plane.
+    fn hex_at(&self, x: f32, y: f32) -> (f32, f32) {
+        let pitch = HEX_ROW_PITCH * self.tile_size;
+        let row = (y / pitch).floor();
+
+        let candidates = (-1..=1).flat_map(|dy| (-1..=1).map(move |dx| (dx, dy)));
+        let nearest = candidates
+            .map(|(dx, dy)| {
+                let row = row + dy as f32;
+                let shift = hex_row_shift(row as isize);
+                let col = (x / self.tile_size - shift).floor() + dx as f32;
+
+                let center_x = (col + shift + 0.5) * self.tile_size;
+                let center_y = row * pitch + HEX_HEIGHT * self.tile_size / 2.;
+                let distance = (x - center_x).powi(2) + (y - center_y).powi(2);
+
+                (col, row, distance)
+            })
+            .min_by(|(.., a), (.., b)| a.total_cmp(b));
+
+        nearest.map_or((-1., -1.), |(col, row, _)| (col, row))
+    }
+}
+
+/// Size of a whole board with `dims` tiles, measured in tile widths,
+/// including the ghost tiles around it if it wraps.
+pub fn board_extent(dims: (usize, usize), topology: Topology, wrapping: bool) -> (f32, f32) {
+    let (cols, rows) = match wrapping {
+        true => (dims.0 + 2, dims.1 + 2),
+        false => dims,
+    };
+
+    match topology {
+        Topology::Square => (cols as f32, rows as f32),
+        Topology::Hex => {
+            let shifted = if rows > 1 { hex_row_shift(1) } else { 0. };
+            let height = rows.saturating_sub(1) as f32 * HEX_ROW_PITCH + HEX_HEIGHT;
+
+            (cols as f32 + shifted, height)
+        }
+    }
+}
+
+/// How far a row of hex tiles is shifted right, in tile widths.
+fn hex_row_shift(y: isize) -> f32 {
+    match y.rem_euclid(2) {
+        0 => 0.,
+        _ => 0.5,
+    }
+}
+
+fn frame_extent(frame: Frame) -> (f32, f32) {
+    board_extent(frame.dims, frame.topology, frame.wrapping)
+}
+
+/// Tile size that fits the whole board into the viewport, up to the maximum.
+fn fit_tile_size(frame: Frame) -> f32 {
+    let (viewport_width, viewport_height) = frame.viewport;
+    let (board_width, board_height) = frame_extent(frame);
+
+    frame
+        .max_tile_size
+        .min(viewport_width / board_width)
+        .min(viewport_height / board_height)
+}
+
+/// Origin of the board when it sits in the middle of the viewport.
+fn centered_origin(frame: Frame, tile_size: f32) -> (f32, f32) {
+    let (viewport_width, viewport_height) = frame.viewport;
+    let (board_width, board_height) = frame_extent(frame);
+
+    // the board itself starts past the ring of ghost tiles
+    let (ghost_width, ghost_height) = match (frame.wrapping, frame.topology) {
+        (false, _) => (0., 0.),
+        (true, Topology::Square) => (tile_size, tile_size),
+        (true, Topology::Hex) => (tile_size, HEX_ROW_PITCH * tile_size),
+    };
+
+    (
+        (viewport_width - board_width * tile_size) / 2. + ghost_width,
+        (viewport_height - board_height * tile_size) / 2. + ghost_height,
+    )
+}
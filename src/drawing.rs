@@ -2,98 +2,517 @@ use notan::draw::*;
 use notan::math::{Mat3, Vec2};
 use notan::prelude::*;
 
-use crate::minefield::{Cover, Mark, Object, Params};
+use enimdnal_core::minefield::{
+    stats, Cover, Mark, MarkCycle, Neighborhood, Object, Params, Topology,
+};
+
+use crate::bindings::{Command, COMMANDS};
+use crate::camera::{self, View};
+use crate::settings::{Chording, Difficulty, MineCounter, Theme};
+use crate::state::controls::ControlsState;
 use crate::state::defeat::{DefeatState, Explosion};
+use crate::state::menu::{Entry, MenuState, ENTRIES};
+use crate::state::options::{OptionsState, Setting, SETTINGS};
+use crate::state::replay::ReplayState;
 use crate::state::{Stage, State};
 
-pub const TILE_SIZE: f32 = 40.;
-pub const HALF_TILE_SIZE: f32 = TILE_SIZE / 2.;
+/// Height of a line in the side panel and the menus,
+/// which keep their layout whatever the tile size.
+pub const UI_UNIT: f32 = 40.;
 pub const UI_WIDTH: f32 = 300.;
 
-const DIMS: (f32, f32) = (TILE_SIZE, TILE_SIZE);
+/// Position and size of the hint button, relative to the side panel.
+pub const HINT_BUTTON: ((f32, f32), (f32, f32)) =
+    ((UI_WIDTH / 4., UI_UNIT * 9.), (UI_WIDTH / 2., UI_UNIT));
+
+/// Height needed to fit everything drawn in the side panel.
+const UI_HEIGHT: f32 = UI_UNIT * 17.;
+
+/// Window size while the difficulty menu is shown.
+pub const MENU_SIZE: (f32, f32) = (UI_WIDTH * 1.5, UI_UNIT * 20.);
+/// Vertical offset of the first menu entry.
+pub const MENU_TOP: f32 = UI_UNIT * 2.5;
+pub const MENU_ROW_HEIGHT: f32 = UI_UNIT;
+
 const STROKE: f32 = 3.;
 
-const OUTLINE_COLOR: Color = Color::from_rgb(0., 0.8, 0.7);
-const WIN_COLOR: Color = Color::GREEN;
+/// Position and size of the replay progress bar, relative to the side panel.
+pub const REPLAY_BAR: ((f32, f32), (f32, f32)) = (
+    (UI_WIDTH / 10., UI_UNIT * 13.),
+    (UI_WIDTH * 0.8, UI_UNIT / 2.),
+);
+
+const OVERLAY_ALPHA: f32 = 0.6;
 
-const MINE_COLOR: Color = Color::BLACK;
-const BLANK_COLOR: Color = Color::WHITE;
-const HINT_COLOR: Color = Color::PINK;
+/// Opacity of the background over the ghost tiles of wrapping boards.
+const GHOST_ALPHA: f32 = 0.5;
 
-const COVER_COLOR: Color = Color::GRAY;
-const FLAG_COLOR: Color = Color::RED;
-const UNSURE_COLOR: Color = Color::BLUE;
+const HINT_SAFE_COLOR: Color = Color::GREEN;
+const HINT_GUESS_COLOR: Color = Color::YELLOW;
 
 const EXPLOSION_COLOR: Color = Color::from_rgb(1., 0.502, 0.);
 const EXPLOSION_STROKE: f32 = STROKE * 2.;
 const EXPLOSION_STROKE_COLOR: Color = Color::BLACK;
 
+/// Colours of a [Theme].
+#[derive(Debug)]
+struct Palette {
+    background: Color,
+    outline: Color,
+    win: Color,
+    mine: Color,
+    blank: Color,
+    hint: Color,
+    hint_text: Color,
+    cover: Color,
+    flag: Color,
+    unsure: Color,
+    cursor: Color,
+}
+
+const CLASSIC: Palette = Palette {
+    background: Color::BLACK,
+    outline: Color::from_rgb(0., 0.8, 0.7),
+    win: Color::GREEN,
+    mine: Color::BLACK,
+    blank: Color::WHITE,
+    hint: Color::PINK,
+    hint_text: Color::BLACK,
+    cover: Color::GRAY,
+    flag: Color::RED,
+    unsure: Color::BLUE,
+    cursor: Color::ORANGE,
+};
+
+const DARK: Palette = Palette {
+    background: Color::from_rgb(0.07, 0.07, 0.09),
+    outline: Color::from_rgb(0.2, 0.2, 0.25),
+    win: Color::from_rgb(0.2, 0.55, 0.3),
+    mine: Color::BLACK,
+    blank: Color::from_rgb(0.35, 0.35, 0.4),
+    hint: Color::from_rgb(0.45, 0.3, 0.45),
+    hint_text: Color::WHITE,
+    cover: Color::from_rgb(0.18, 0.18, 0.22),
+    flag: Color::from_rgb(0.75, 0.2, 0.2),
+    unsure: Color::from_rgb(0.25, 0.4, 0.75),
+    cursor: Color::ORANGE,
+};
+
+const CONTRAST: Palette = Palette {
+    background: Color::BLACK,
+    outline: Color::WHITE,
+    win: Color::GREEN,
+    mine: Color::BLACK,
+    blank: Color::WHITE,
+    hint: Color::YELLOW,
+    hint_text: Color::BLACK,
+    cover: Color::from_rgb(0.35, 0.35, 0.35),
+    flag: Color::RED,
+    unsure: Color::from_rgb(0., 0.6, 1.),
+    cursor: Color::MAGENTA,
+};
+
+impl Theme {
+    fn palette(self) -> &'static Palette {
+        match self {
+            Theme::Classic => &CLASSIC,
+            Theme::Dark => &DARK,
+            Theme::Contrast => &CONTRAST,
+        }
+    }
+}
+
 pub fn draw(gfx: &mut Graphics, state: &mut State) {
     let mut draw = gfx.create_draw();
 
-    draw.clear(Color::BLACK);
+    draw.clear(state.settings().theme.palette().background);
 
-    draw_ui(&mut draw, state);
+    match state.stage() {
+        Stage::Menu(menu) => {
+            draw_menu(&mut draw, state, menu);
+            gfx.render(&draw);
+            return;
+        }
+        Stage::Controls(controls) => {
+            draw_controls(&mut draw, state, controls);
+            gfx.render(&draw);
+            return;
+        }
+        Stage::Options(options) => {
+            draw_options(&mut draw, state, options);
+            gfx.render(&draw);
+            return;
+        }
+        _ => (),
+    }
+
+    let view = state.view();
 
     match state.stage() {
         Stage::Paused => draw_paused(&mut draw, state),
-        _ => draw_board(&mut draw, state),
+        _ => draw_board(&mut draw, state, view),
     }
 
     if let Stage::Defeat(defeat_state) = state.stage() {
-        draw_explosions(&mut draw, defeat_state);
+        draw_explosions(&mut draw, defeat_state, view);
     }
 
+    // the side panel goes on top of whatever part of the board is under it
+    draw_ui(&mut draw, state);
+
     gfx.render(&draw);
 }
 
-pub fn board_dims(params: Params) -> (f32, f32) {
-    (
-        params.width as f32 * TILE_SIZE,
-        params.height as f32 * TILE_SIZE,
+pub fn board_dims(params: Params, tile_size: f32) -> (f32, f32) {
+    let dims = (params.width(), params.height());
+    let (width, height) = camera::board_extent(dims, params.topology(), params.wrapping());
+
+    (width * tile_size, height * tile_size)
+}
+
+/// Window size needed to fit a board with `params` next to the side panel.
+pub fn window_size(params: Params, tile_size: f32) -> (i32, i32) {
+    let (width, height) = board_dims(params, tile_size);
+
+    ((width + UI_WIDTH) as _, f32::max(height, UI_HEIGHT) as _)
+}
+
+fn draw_menu(draw: &mut Draw, state: &State, menu: &MenuState) {
+    let palette = state.settings().theme.palette();
+    let (width, _) = MENU_SIZE;
+
+    draw.text(state.font(), "ENIMDNAL")
+        .color(Color::WHITE)
+        .size(40.)
+        .position(width / 2., UI_UNIT * 1.2)
+        .h_align_center()
+        .v_align_middle();
+
+    for (i, &entry) in ENTRIES.iter().enumerate() {
+        let label = match entry {
+            Entry::Continue => match state.saved_game() {
+                Some(saved) => format!(
+                    "Continue ({}x{}, {})",
+                    saved.board.params().width(),
+                    saved.board.params().height(),
+                    format_time(saved.run_timer_milisec)
+                ),
+                None => "Continue".to_string(),
+            },
+            Entry::Beginner => "Beginner".to_string(),
+            Entry::Intermediate => "Intermediate".to_string(),
+            Entry::Expert => "Expert".to_string(),
+            Entry::Custom => "Custom".to_string(),
+            Entry::Width => format!("  width: {}", menu.width),
+            Entry::Height => format!("  height: {}", menu.height),
+            Entry::Mines => format!("  mines: {}", menu.mines),
+            Entry::NoGuess => match menu.no_guess {
+                true => "No guessing: on".to_string(),
+                false => "No guessing: off".to_string(),
+            },
+            Entry::Tiles => match menu.topology {
+                Topology::Square => "Tiles: square".to_string(),
+                Topology::Hex => "Tiles: hex".to_string(),
+            },
+            Entry::Wrapping => match menu.wrapping {
+                true => "Wrap around: on".to_string(),
+                false => "Wrap around: off".to_string(),
+            },
+            Entry::Neighbors => match menu.neighborhood {
+                Neighborhood::Adjacent => "Neighbors: adjacent".to_string(),
+                Neighborhood::Orthogonal => "Neighbors: orthogonal".to_string(),
+                Neighborhood::Knight => "Neighbors: knight's move".to_string(),
+                Neighborhood::Radius(radius) => format!("Neighbors: radius {radius}"),
+                Neighborhood::Custom(_) => "Neighbors: custom".to_string(),
+            },
+            Entry::Stacking => match menu.distribution.stack() {
+                1 => "Mines per tile: 1".to_string(),
+                stack => format!("Mines per tile: up to {stack}"),
+            },
+            Entry::Options => "Options".to_string(),
+            Entry::Controls => "Controls".to_string(),
+        };
+
+        let row_y = MENU_TOP + i as f32 * MENU_ROW_HEIGHT;
+
+        if i == menu.selected {
+            draw.rect((UI_UNIT, row_y), (width - UI_UNIT * 2., MENU_ROW_HEIGHT))
+                .color(palette.outline)
+                .stroke(STROKE);
+        }
+
+        let unavailable = entry == Entry::Continue && state.saved_game().is_none();
+        let color = if unavailable {
+            Color::GRAY
+        } else {
+            Color::WHITE
+        };
+
+        draw.text(state.font(), &label)
+            .color(color)
+            .size(20.)
+            .position(UI_UNIT * 1.5, row_y + MENU_ROW_HEIGHT / 2.)
+            .v_align_middle();
+    }
+
+    let bottom = MENU_TOP + ENTRIES.len() as f32 * MENU_ROW_HEIGHT;
+
+    if let Some(error) = &menu.error {
+        draw.text(state.font(), error)
+            .color(palette.flag)
+            .size(16.)
+            .position(width / 2., bottom + UI_UNIT * 0.5)
+            .h_align_center()
+            .v_align_middle();
+    }
+
+    draw.text(
+        state.font(),
+        "[Up/Down] select  [Left/Right] or digits change\n[Enter] start",
     )
+    .color(Color::GRAY)
+    .size(16.)
+    .position(width / 2., bottom + UI_UNIT * 1.4)
+    .h_align_center()
+    .v_align_middle();
 }
 
-fn draw_board(draw: &mut Draw, state: &State) {
-    let (cols, rows) = state.board().dims();
+fn draw_controls(draw: &mut Draw, state: &State, controls: &ControlsState) {
+    let palette = state.settings().theme.palette();
+    let (width, _) = MENU_SIZE;
+    let bindings = state.bindings();
 
-    for y in 0..rows {
-        for x in 0..cols {
-            draw_tile(draw, state, x, y);
+    draw.text(state.font(), "CONTROLS")
+        .color(Color::WHITE)
+        .size(40.)
+        .position(width / 2., UI_UNIT * 1.2)
+        .h_align_center()
+        .v_align_middle();
+
+    for (i, &command) in COMMANDS.iter().enumerate() {
+        let row_y = MENU_TOP + i as f32 * MENU_ROW_HEIGHT;
+        let selected = i == controls.selected;
+
+        if selected {
+            draw.rect((UI_UNIT, row_y), (width - UI_UNIT * 2., MENU_ROW_HEIGHT))
+                .color(palette.outline)
+                .stroke(STROKE);
         }
+
+        let color = match bindings.conflict(command) {
+            Some(_) => palette.flag,
+            None => Color::WHITE,
+        };
+
+        draw.text(state.font(), &format!("{command:?}"))
+            .color(color)
+            .size(20.)
+            .position(UI_UNIT * 1.5, row_y + MENU_ROW_HEIGHT / 2.)
+            .v_align_middle();
+
+        let triggers = match selected && controls.capturing {
+            true => "press a key or button...".to_string(),
+            false => bindings.describe(command),
+        };
+
+        draw.text(state.font(), &triggers)
+            .color(color)
+            .size(16.)
+            .position(width - UI_UNIT * 1.5, row_y + MENU_ROW_HEIGHT / 2.)
+            .h_align_right()
+            .v_align_middle();
+    }
+
+    let bottom = MENU_TOP + COMMANDS.len() as f32 * MENU_ROW_HEIGHT;
+    let message = controls
+        .error
+        .clone()
+        .or_else(|| bindings.conflict(COMMANDS[controls.selected]));
+
+    if let Some(message) = message {
+        draw.text(state.font(), &message)
+            .color(palette.flag)
+            .size(16.)
+            .position(width / 2., bottom + UI_UNIT * 0.5)
+            .h_align_center()
+            .v_align_middle();
     }
+
+    draw.text(
+        state.font(),
+        "[Up/Down] select  [Enter] rebind  [Back] default\n[Esc] save and return",
+    )
+    .color(Color::GRAY)
+    .size(16.)
+    .position(width / 2., bottom + UI_UNIT * 1.4)
+    .h_align_center()
+    .v_align_middle();
 }
 
-fn draw_tile(draw: &mut Draw, state: &State, x: usize, y: usize) {
-    let screen_x = x as f32 * TILE_SIZE;
-    let screen_y = y as f32 * TILE_SIZE;
-    let pos = (screen_x, screen_y);
+fn draw_options(draw: &mut Draw, state: &State, options: &OptionsState) {
+    let palette = state.settings().theme.palette();
+    let (width, _) = MENU_SIZE;
+    let settings = state.settings();
+    let on_off = |enabled: bool| match enabled {
+        true => "on".to_string(),
+        false => "off".to_string(),
+    };
+
+    draw.text(state.font(), "OPTIONS")
+        .color(Color::WHITE)
+        .size(40.)
+        .position(width / 2., UI_UNIT * 1.2)
+        .h_align_center()
+        .v_align_middle();
+
+    for (i, &setting) in SETTINGS.iter().enumerate() {
+        let (name, value) = match setting {
+            Setting::Difficulty => (
+                "Difficulty",
+                match settings.difficulty {
+                    Difficulty::Beginner => "beginner".to_string(),
+                    Difficulty::Intermediate => "intermediate".to_string(),
+                    Difficulty::Expert => "expert".to_string(),
+                },
+            ),
+            Setting::Theme => (
+                "Theme",
+                match settings.theme {
+                    Theme::Classic => "classic".to_string(),
+                    Theme::Dark => "dark".to_string(),
+                    Theme::Contrast => "high contrast".to_string(),
+                },
+            ),
+            Setting::TileSize => ("Tile size", format!("{} px", settings.tile_size)),
+            Setting::Marks => (
+                "Marks",
+                match settings.marks {
+                    MarkCycle::FlagOnly => "flag".to_string(),
+                    MarkCycle::FlagUnsure => "flag, unsure".to_string(),
+                    MarkCycle::UnsureFirst => "unsure, flag".to_string(),
+                },
+            ),
+            Setting::Chording => (
+                "Chording",
+                match settings.chording {
+                    Chording::Uncover => "on uncover".to_string(),
+                    Chording::Dedicated => "chord only".to_string(),
+                    Chording::Off => "off".to_string(),
+                },
+            ),
+            Setting::Counter => (
+                "Mine counter",
+                match settings.counter {
+                    MineCounter::Flags => "flags / mines".to_string(),
+                    MineCounter::Remaining => "mines left".to_string(),
+                },
+            ),
+            Setting::Probabilities => (
+                "Mine probabilities",
+                on_off(settings.assistance.probabilities),
+            ),
+            Setting::Hints => ("Hints", on_off(settings.assistance.hints)),
+            Setting::Practice => ("Practice mode", on_off(settings.assistance.practice)),
+        };
+
+        let row_y = MENU_TOP + i as f32 * MENU_ROW_HEIGHT;
+
+        if i == options.selected {
+            draw.rect((UI_UNIT, row_y), (width - UI_UNIT * 2., MENU_ROW_HEIGHT))
+                .color(palette.outline)
+                .stroke(STROKE);
+        }
+
+        draw.text(state.font(), name)
+            .color(Color::WHITE)
+            .size(20.)
+            .position(UI_UNIT * 1.5, row_y + MENU_ROW_HEIGHT / 2.)
+            .v_align_middle();
+
+        draw.text(state.font(), &value)
+            .color(Color::WHITE)
+            .size(16.)
+            .position(width - UI_UNIT * 1.5, row_y + MENU_ROW_HEIGHT / 2.)
+            .h_align_right()
+            .v_align_middle();
+    }
+
+    let bottom = MENU_TOP + SETTINGS.len() as f32 * MENU_ROW_HEIGHT;
+
+    draw.text(
+        state.font(),
+        "[Up/Down] select  [Left/Right] change\n[Esc] save and return",
+    )
+    .color(Color::GRAY)
+    .size(16.)
+    .position(width / 2., bottom + UI_UNIT * 1.4)
+    .h_align_center()
+    .v_align_middle();
+}
+
+fn draw_board(draw: &mut Draw, state: &State, view: View) {
+    let (cols, rows) = view.visible(state.frame());
+
+    for y in rows {
+        for x in cols.clone() {
+            draw_tile(draw, state, view, TileOutline::new(view, x, y), (x, y));
+        }
+    }
+
+    // faded copies of the tiles across the edges of wrapping boards,
+    // so that the hints along the seams can be read
+    let background = state.settings().theme.palette().background;
+    let ghost_shade = Color {
+        a: GHOST_ALPHA,
+        ..background
+    };
+
+    for (center, (x, y)) in view.ghosts(state.board().dims()) {
+        let outline = TileOutline::at(view, center);
+
+        draw_tile(draw, state, view, outline, (x, y));
+        outline.fill(draw, ghost_shade);
+    }
+}
+
+fn draw_tile(
+    draw: &mut Draw,
+    state: &State,
+    view: View,
+    outline: TileOutline,
+    (x, y): (usize, usize),
+) {
+    let palette = state.settings().theme.palette();
+    let tile_size = view.tile_size;
+
+    // thinner lines on tiles zoomed out too far to fit them
+    let stroke = f32::min(STROKE, tile_size / 8.);
 
     let tile = state.board().tile(x, y);
     let cover = tile.cover();
     let object = tile.object();
 
     let mut fill_color = match (cover, object) {
-        (Cover::Up(Mark::None), _) => COVER_COLOR,
-        (Cover::Up(Mark::Flag), _) => FLAG_COLOR,
-        (Cover::Up(Mark::Unsure), _) => UNSURE_COLOR,
-        (Cover::Down, Object::Blank) => BLANK_COLOR,
-        (Cover::Down, Object::Hint(_)) => HINT_COLOR,
-        (Cover::Down, Object::Mine) => MINE_COLOR,
+        (Cover::Up(Mark::None), _) => palette.cover,
+        (Cover::Up(Mark::Flag(_)), _) => palette.flag,
+        (Cover::Up(Mark::Unsure), _) => palette.unsure,
+        (Cover::Down, Object::Blank) => palette.blank,
+        (Cover::Down, Object::Hint(_)) => palette.hint,
+        (Cover::Down, Object::Mine(_)) => palette.mine,
     };
 
-    match state.stage() {
-        Stage::Defeat(_) => {
-            if let Object::Mine = object {
-                fill_color = MINE_COLOR;
-            }
-        }
-        Stage::Victory => {
-            if let Cover::Up(_) = cover {
-                fill_color = WIN_COLOR;
-            }
-        }
-        _ => (),
+    let mines_revealed = match state.stage() {
+        Stage::Defeat(_) => true,
+        Stage::Replay(_) => state.board().is_defeat(),
+        _ => cover == Cover::Down,
+    };
+
+    if mines_revealed && tile.is_mine() {
+        fill_color = palette.mine;
+    }
+
+    if let (Stage::Victory, Cover::Up(_)) = (state.stage(), cover) {
+        fill_color = palette.win;
     }
 
     if let Some(hover_coords) = state.hover_index() {
@@ -102,19 +521,153 @@ fn draw_tile(draw: &mut Draw, state: &State, x: usize, y: usize) {
         }
     }
 
-    draw.rect(pos, DIMS).color(fill_color);
-    draw.rect(pos, DIMS).color(OUTLINE_COLOR).stroke(STROKE);
+    outline.fill(draw, fill_color);
+
+    if let (Stage::Playing, Some(chance)) = (state.stage(), state.probability(x, y)) {
+        draw_probability(draw, state, view, outline, chance);
+    }
+
+    outline.stroke(draw, palette.outline, stroke);
+
+    if let (Stage::Playing, Some(hint)) = (state.stage(), state.hint()) {
+        if hint.pos == (x, y) {
+            let color = if hint.certain {
+                HINT_SAFE_COLOR
+            } else {
+                HINT_GUESS_COLOR
+            };
+
+            outline.inset(stroke).stroke(draw, color, stroke);
+        }
+    }
+
+    if let (Stage::Playing, Some(cursor)) = (state.stage(), state.cursor()) {
+        if cursor == (x, y) {
+            outline.stroke(draw, palette.cursor, stroke * 2.);
+        }
+    }
 
     if let (Cover::Down, Object::Hint(n)) = (cover, object) {
+        let (center_x, center_y) = outline.center;
+
         draw.text(state.font(), &n.to_string())
-            .color(Color::BLACK)
-            .size(26.0)
-            .position(screen_x + HALF_TILE_SIZE, screen_y + HALF_TILE_SIZE)
+            .color(palette.hint_text)
+            .size(tile_size * 0.65)
+            .position(center_x, center_y)
+            .h_align_center()
+            .v_align_middle();
+    }
+
+    // stacks of mines or flags are labeled with their size
+    let stack = match mines_revealed && tile.is_mine() {
+        true => tile.mines(),
+        false => tile.flags(),
+    };
+
+    if stack > 1 {
+        let (center_x, center_y) = outline.center;
+
+        draw.text(state.font(), &format!("×{stack}"))
+            .color(palette.hint_text)
+            .size(tile_size * 0.45)
+            .position(center_x, center_y)
             .h_align_center()
             .v_align_middle();
     }
 }
 
+/// Shade a covered tile from green (certainly safe) to red (certainly a mine).
+fn draw_probability(draw: &mut Draw, state: &State, view: View, outline: TileOutline, chance: f64) {
+    let chance = chance as f32;
+    let shade = Color::from_rgba(chance, 1. - chance, 0., OVERLAY_ALPHA);
+    let percent = format!("{:.0}%", chance * 100.);
+    let (center_x, center_y) = outline.center;
+
+    outline.fill(draw, shade);
+    draw.text(state.font_mono(), &percent)
+        .color(Color::BLACK)
+        .size(view.tile_size * 0.3)
+        .position(center_x, center_y)
+        .h_align_center()
+        .v_align_middle();
+}
+
+/// Shape of a tile on the screen, whatever the board topology.
+#[derive(Debug, Clone, Copy)]
+struct TileOutline {
+    topology: Topology,
+    center: (f32, f32),
+
+    /// Size of the box around the tile.
+    dims: (f32, f32),
+}
+
+impl TileOutline {
+    fn new(view: View, x: usize, y: usize) -> Self {
+        Self::at(view, view.tile_center(x, y))
+    }
+
+    fn at(view: View, center: (f32, f32)) -> Self {
+        Self {
+            topology: view.topology,
+            center,
+            dims: view.tile_dims(),
+        }
+    }
+
+    /// The same shape, with every side moved `by` pixels inwards,
+    /// or outwards for a negative `by`.
+    fn inset(self, by: f32) -> Self {
+        // hex corners are further out than the sides,
+        // so the box around the tile shrinks more in height
+        let (width, height) = self.dims;
+        let height_inset = match self.topology {
+            Topology::Square => by,
+            Topology::Hex => by * height / width,
+        };
+
+        Self {
+            dims: (width - by * 2., height - height_inset * 2.),
+            ..self
+        }
+    }
+
+    fn fill(self, draw: &mut Draw, color: Color) {
+        let (center_x, center_y) = self.center;
+        let (width, height) = self.dims;
+
+        match self.topology {
+            Topology::Square => {
+                let corner = (center_x - width / 2., center_y - height / 2.);
+                draw.rect(corner, self.dims).color(color);
+            }
+            Topology::Hex => {
+                draw.polygon(6, height / 2.)
+                    .position(center_x, center_y)
+                    .color(color);
+            }
+        }
+    }
+
+    fn stroke(self, draw: &mut Draw, color: Color, width: f32) {
+        let (center_x, center_y) = self.center;
+        let (tile_width, tile_height) = self.dims;
+
+        match self.topology {
+            Topology::Square => {
+                let corner = (center_x - tile_width / 2., center_y - tile_height / 2.);
+                draw.rect(corner, self.dims).color(color).stroke(width);
+            }
+            Topology::Hex => {
+                draw.polygon(6, tile_height / 2.)
+                    .position(center_x, center_y)
+                    .color(color)
+                    .stroke(width);
+            }
+        }
+    }
+}
+
 fn hover_color(color: &mut Color) {
     let Color { r, g, b, .. } = color;
 
@@ -123,12 +676,13 @@ fn hover_color(color: &mut Color) {
     *b *= 0.8;
 }
 
+/// Cover the whole board area, so that the board cannot be studied while paused.
 fn draw_paused(draw: &mut Draw, state: &State) {
-    let (cols, rows) = state.board().dims();
-    let size @ (width, height) = (cols as f32 * TILE_SIZE, rows as f32 * TILE_SIZE);
+    let palette = state.settings().theme.palette();
+    let size @ (width, height) = state.frame().viewport;
 
-    draw.rect((0., 0.), size).color(COVER_COLOR);
-    draw.rect((0., 0.), size).color(OUTLINE_COLOR).stroke(3.);
+    draw.rect((0., 0.), size).color(palette.cover);
+    draw.rect((0., 0.), size).color(palette.outline).stroke(3.);
 
     draw.text(state.font(), "PAUSED")
         .color(Color::WHITE)
@@ -139,50 +693,342 @@ fn draw_paused(draw: &mut Draw, state: &State) {
 }
 
 fn draw_ui(draw: &mut Draw, state: &State) {
-    let (cols, _) = state.board().dims();
-
-    draw.transform().push(Mat3::from_translation(Vec2::new(
-        cols as f32 * TILE_SIZE,
-        0.,
-    )));
+    let palette = state.settings().theme.palette();
+    let panel_x = state.panel_x();
+    let (_, height) = state.frame().viewport;
 
-    let elapsed = state.run_timer_milisec();
+    draw.rect((panel_x, 0.), (UI_WIDTH, height))
+        .color(palette.background);
 
-    let milis = elapsed % 1000;
-    let secs = (elapsed / 1000) % 60;
-    let mins = elapsed / 60_000;
+    draw.transform()
+        .push(Mat3::from_translation(Vec2::new(panel_x, 0.)));
 
-    let time = format!("{:02}:{:02}.{:03}", mins, secs, milis);
+    let time = format_time(state.run_timer_milisec());
 
     draw.text(state.font_mono(), &time)
         .color(Color::WHITE)
         .size(30.)
-        .position(UI_WIDTH / 2., TILE_SIZE)
+        .position(UI_WIDTH / 2., UI_UNIT)
         .h_align_center()
         .v_align_middle();
 
-    let flags = state.board().flags();
-    let mines = state.board().mines();
-
-    let flag_counter = format!("{:03} / {:03}", flags, mines);
+    let board = state.board();
+    let mine_counter = match state.settings().counter {
+        MineCounter::Flags => format!("{:03} / {:03}", board.flags(), board.mines()),
+        MineCounter::Remaining => format!("{:03}", board.mines_remaining()),
+    };
 
-    draw.text(state.font_mono(), &flag_counter)
+    draw.text(state.font_mono(), &mine_counter)
         .color(Color::WHITE)
         .size(30.)
-        .position(UI_WIDTH / 2., TILE_SIZE * 3.)
+        .position(UI_WIDTH / 2., UI_UNIT * 3.)
+        .h_align_center()
+        .v_align_middle();
+
+    let notice = if state.is_generating() {
+        Some("placing mines...")
+    } else if board.is_no_guess_fallback() {
+        Some("may need guessing")
+    } else {
+        None
+    };
+
+    if let Some(notice) = notice {
+        draw.text(state.font(), notice)
+            .color(palette.flag)
+            .size(16.)
+            .position(UI_WIDTH / 2., UI_UNIT * 4.)
+            .h_align_center()
+            .v_align_middle();
+    }
+
+    let seed = format!("seed {}", state.board().seed());
+
+    draw.text(state.font_mono(), &seed)
+        .color(Color::WHITE)
+        .size(16.)
+        .position(UI_WIDTH / 2., UI_UNIT * 5.)
         .h_align_center()
         .v_align_middle();
 
+    draw.text(state.font(), "[C] copy seed")
+        .color(Color::GRAY)
+        .size(16.)
+        .position(UI_WIDTH / 2., UI_UNIT * 5.6)
+        .h_align_center()
+        .v_align_middle();
+
+    if state.settings().assistance.probabilities {
+        draw.text(state.font(), "[P] mine probabilities")
+            .color(Color::GRAY)
+            .size(16.)
+            .position(UI_WIDTH / 2., UI_UNIT * 6.6)
+            .h_align_center()
+            .v_align_middle();
+    }
+
+    match state.stage() {
+        Stage::Victory | Stage::Defeat(_) => {
+            draw_scores(draw, state);
+            draw_game_stats(draw, state);
+        }
+        _ => draw_assistance(draw, state),
+    }
+
+    if state.is_assisted() {
+        draw.text(state.font(), "UNRANKED")
+            .color(palette.flag)
+            .size(20.)
+            .position(UI_WIDTH / 2., UI_UNIT * 7.6)
+            .h_align_center()
+            .v_align_middle();
+    }
+
+    if let Stage::Replay(viewer) = state.stage() {
+        draw_replay_controls(draw, state, viewer);
+    }
+
     draw.transform().pop();
 }
 
-fn draw_explosions(draw: &mut Draw, defeat_state: &DefeatState) {
+/// Hint button and practice mode status, for the features enabled in the settings.
+fn draw_assistance(draw: &mut Draw, state: &State) {
+    let palette = state.settings().theme.palette();
+    let assistance = state.settings().assistance;
+
+    if assistance.hints {
+        let hints = format!("hints used: {}", state.hints_used());
+
+        draw.text(state.font(), &hints)
+            .color(Color::WHITE)
+            .size(16.)
+            .position(UI_WIDTH / 2., UI_UNIT * 8.4)
+            .h_align_center()
+            .v_align_middle();
+
+        let (button_pos, button_size) = HINT_BUTTON;
+        let (button_x, button_y) = button_pos;
+        let (button_width, button_height) = button_size;
+
+        draw.rect(button_pos, button_size).color(palette.cover);
+        draw.rect(button_pos, button_size)
+            .color(palette.outline)
+            .stroke(STROKE);
+        let hint = format!("HINT [{}]", state.bindings().label(Command::Hint));
+
+        draw.text(state.font(), &hint)
+            .color(Color::WHITE)
+            .size(20.)
+            .position(button_x + button_width / 2., button_y + button_height / 2.)
+            .h_align_center()
+            .v_align_middle();
+
+        if state.hint().is_some_and(|hint| hint.misflagged) {
+            draw.text(state.font(), "this flag is on a safe tile")
+                .color(palette.flag)
+                .size(16.)
+                .position(UI_WIDTH / 2., UI_UNIT * 10.4)
+                .h_align_center()
+                .v_align_middle();
+        }
+    }
+
+    if assistance.practice || state.is_practice() {
+        let practice = if state.is_practice() {
+            "[F2] practice: on"
+        } else {
+            "[F2] practice: off"
+        };
+
+        draw.text(state.font(), practice)
+            .color(Color::GRAY)
+            .size(16.)
+            .position(UI_WIDTH / 2., UI_UNIT * 11.)
+            .h_align_center()
+            .v_align_middle();
+    }
+
+    if state.is_practice() {
+        let undo = format!(
+            "[{}] undo  [{}] redo",
+            state.bindings().label(Command::Undo),
+            state.bindings().label(Command::Redo)
+        );
+
+        draw.text(state.font(), &undo)
+            .color(Color::GRAY)
+            .size(16.)
+            .position(UI_WIDTH / 2., UI_UNIT * 11.6)
+            .h_align_center()
+            .v_align_middle();
+    }
+
+    if let Stage::Playing | Stage::Paused = state.stage() {
+        let best = match state.scores().personal_best(state.board().params()) {
+            Some(record) => format!("personal best: {}", format_time(record.time_milisec)),
+            None => "personal best: none".to_string(),
+        };
+
+        draw.text(state.font(), &best)
+            .color(Color::WHITE)
+            .size(16.)
+            .position(UI_WIDTH / 2., UI_UNIT * 12.4)
+            .h_align_center()
+            .v_align_middle();
+
+        let bindings = state.bindings();
+        let cursor_help = format!(
+            "[Arrows/WASD/HJKL] cursor\n[Shift] to edge  [Ctrl] next covered\n[{}] uncover  [{}] flag  [{}] chord",
+            bindings.label(Command::Uncover),
+            bindings.label(Command::Flag),
+            bindings.label(Command::Chord)
+        );
+
+        draw.text(state.font(), &cursor_help)
+            .color(Color::GRAY)
+            .size(16.)
+            .position(UI_WIDTH / 2., UI_UNIT * 14.2)
+            .h_align_center()
+            .v_align_middle();
+    }
+}
+
+/// Table of the fastest ranked wins on the current difficulty.
+fn draw_scores(draw: &mut Draw, state: &State) {
+    let palette = state.settings().theme.palette();
+    draw.text(state.font(), "BEST TIMES")
+        .color(Color::WHITE)
+        .size(20.)
+        .position(UI_WIDTH / 2., UI_UNIT * 8.4)
+        .h_align_center()
+        .v_align_middle();
+
+    let records = state.scores().top(state.board().params());
+
+    if records.is_empty() {
+        draw.text(state.font(), "no ranked wins yet")
+            .color(Color::GRAY)
+            .size(16.)
+            .position(UI_WIDTH / 2., UI_UNIT * 9.2)
+            .h_align_center()
+            .v_align_middle();
+
+        return;
+    }
+
+    draw.text(state.font_mono(), "   time      3BV  eff  date")
+        .color(Color::GRAY)
+        .size(12.)
+        .position(UI_WIDTH / 20., UI_UNIT * 9.)
+        .v_align_middle();
+
+    for (i, record) in records.iter().enumerate() {
+        let row = format!(
+            "{:>2} {} {:>4} {:>3.0}% {}",
+            i + 1,
+            format_time(record.time_milisec),
+            record.bbbv,
+            record.efficiency() * 100.,
+            record.date(),
+        );
+
+        // the freshly set record stands out
+        let is_current =
+            record.seed == state.board().seed() && record.time_milisec == state.run_timer_milisec();
+        let color = if is_current {
+            palette.win
+        } else {
+            Color::WHITE
+        };
+
+        draw.text(state.font_mono(), &row)
+            .color(color)
+            .size(12.)
+            .position(UI_WIDTH / 20., UI_UNIT * (9.5 + i as f32 * 0.5))
+            .v_align_middle();
+    }
+}
+
+/// Speed and efficiency of the run that has just ended.
+fn draw_game_stats(draw: &mut Draw, state: &State) {
+    let board = state.board();
+    let bbbv = stats::bbbv(board);
+    let solved = stats::solved_bbbv(board);
+    let clicks = board.clicks();
+    let per_second = stats::bbbv_per_second(solved, state.run_timer_milisec());
+    let efficiency = stats::efficiency(solved, clicks.total() as usize);
+
+    let lines = [
+        format!("3BV {solved}/{bbbv}   3BV/s {per_second:.2}"),
+        format!(
+            "clicks {} (L {} R {} C {})",
+            clicks.total(),
+            clicks.left,
+            clicks.right,
+            clicks.chord
+        ),
+        format!("efficiency {:.0}%", efficiency * 100.),
+    ];
+
+    for (i, line) in lines.iter().enumerate() {
+        draw.text(state.font(), line)
+            .color(Color::WHITE)
+            .size(16.)
+            .position(UI_WIDTH / 2., UI_UNIT * (14.8 + i as f32 * 0.6))
+            .h_align_center()
+            .v_align_middle();
+    }
+}
+
+fn format_time(milisec: u32) -> String {
+    let milis = milisec % 1000;
+    let secs = (milisec / 1000) % 60;
+    let mins = milisec / 60_000;
+
+    format!("{:02}:{:02}.{:03}", mins, secs, milis)
+}
+
+/// Playback status and a progress bar that doubles as a scrubber.
+fn draw_replay_controls(draw: &mut Draw, state: &State, viewer: &ReplayState) {
+    let palette = state.settings().theme.palette();
+    let status = if viewer.paused {
+        "REPLAY (paused)".to_string()
+    } else {
+        format!("REPLAY x{}", viewer.speed())
+    };
+
+    draw.text(state.font(), &status)
+        .color(Color::WHITE)
+        .size(20.)
+        .position(UI_WIDTH / 2., UI_UNIT * 12.4)
+        .h_align_center()
+        .v_align_middle();
+
+    let (bar_pos, (bar_width, bar_height)) = REPLAY_BAR;
+
+    draw.rect(bar_pos, (bar_width, bar_height))
+        .color(palette.cover);
+    draw.rect(bar_pos, (bar_width * viewer.progress(), bar_height))
+        .color(palette.outline);
+
+    draw.text(
+        state.font(),
+        "[Space] pause  [Up/Down] speed\n[Left/Right] seek  [Esc] menu",
+    )
+    .color(Color::GRAY)
+    .size(16.)
+    .position(UI_WIDTH / 2., UI_UNIT * 14.3)
+    .h_align_center()
+    .v_align_middle();
+}
+
+fn draw_explosions(draw: &mut Draw, defeat_state: &DefeatState, view: View) {
     for explosion in &defeat_state.explosions {
-        draw_explosion(draw, explosion, defeat_state.elapsed_milisec);
+        draw_explosion(draw, explosion, defeat_state.elapsed_milisec(), view);
     }
 }
 
-fn draw_explosion(draw: &mut Draw, explosion: &Explosion, elapsed: u32) {
+fn draw_explosion(draw: &mut Draw, explosion: &Explosion, elapsed: u32, view: View) {
     const ANIMATION_DURATION: f32 = 100.;
 
     let Some(elapsed) = u32::checked_sub(elapsed, explosion.delay) else {
@@ -191,19 +1037,13 @@ fn draw_explosion(draw: &mut Draw, explosion: &Explosion, elapsed: u32) {
 
     let progress = elapsed as f32 / ANIMATION_DURATION;
     let magnify = gauss(progress, 3., 0., 1.);
-    let shift = TILE_SIZE * magnify;
+    let shift = view.tile_size * magnify;
 
     let (expl_x, expl_y) = explosion.pos;
-    let position = (
-        TILE_SIZE * expl_x as f32 - shift / 2.,
-        TILE_SIZE * expl_y as f32 - shift / 2.,
-    );
-    let size = (TILE_SIZE + shift, TILE_SIZE + shift);
-
-    draw.rect(position, size).color(EXPLOSION_COLOR);
-    draw.rect(position, size)
-        .color(EXPLOSION_STROKE_COLOR)
-        .stroke(EXPLOSION_STROKE);
+    let outline = TileOutline::new(view, expl_x, expl_y).inset(-shift / 2.);
+
+    outline.fill(draw, EXPLOSION_COLOR);
+    outline.stroke(draw, EXPLOSION_STROKE_COLOR, EXPLOSION_STROKE);
 }
 
 fn gauss(x: f32, a: f32, b: f32, c: f32) -> f32 {
@@ -1,26 +1,87 @@
 #![allow(clippy::main_recursion)]
 
+pub(crate) mod bindings;
+pub(crate) mod camera;
 pub(crate) mod drawing;
-pub(crate) mod minefield;
-pub(crate) mod random;
+pub(crate) mod replays;
+pub(crate) mod save;
+pub(crate) mod scores;
+pub(crate) mod settings;
 pub(crate) mod state;
 
+use std::path::PathBuf;
+
 use notan::draw::*;
+use notan::log::LogConfig;
 use notan::prelude::*;
 
-use drawing::UI_WIDTH;
+use enimdnal_core::random::Seed;
+use enimdnal_core::replay::Replay;
+
+#[derive(Debug, Default)]
+struct Args {
+    seed: Option<Seed>,
+    no_guess: bool,
+    replay: Option<PathBuf>,
+}
 
 #[notan_main]
 fn main() -> Result<(), String> {
-    let difficulty = minefield::EXPERT;
-    let (width, height) = drawing::board_dims(difficulty);
+    let args = parse_args()?;
+    let replay = match args.replay {
+        Some(path) => {
+            let replay = Replay::load(&path)
+                .map_err(|e| format!("Failed to load replay {}: {}", path.display(), e))?;
+            Some(replay)
+        }
+        None => None,
+    };
+    // replays get the window resized to fit them once the settings are loaded
+    let (width, height) = drawing::MENU_SIZE;
     let win = WindowConfig::default()
         .title("Enimdnal")
-        .size(width as i32 + UI_WIDTH as i32, height as _);
-    notan::init_with(state::setup)
-        .update(state::update)
-        .draw(drawing::draw)
-        .add_config(win)
-        .add_config(DrawConfig)
-        .build()
+        .size(width as _, height as _)
+        .min_size(width as _, height as _)
+        .resizable(true);
+    let Args { seed, no_guess, .. } = args;
+    notan::init_with(move |app: &mut App, gfx: &mut Graphics| {
+        state::setup(app, gfx, seed, no_guess, replay)
+    })
+    .event(state::event)
+    .update(state::update)
+    .draw(drawing::draw)
+    .add_config(win)
+    .add_config(DrawConfig)
+    .add_config(LogConfig::default())
+    .build()
+}
+
+/// Supported arguments:
+///
+/// - `--seed <HEX>` to play a specific, shared board
+/// - `--no-guess` to preselect boards solvable without guessing in the menu
+/// - `--replay <PATH>` to watch a recorded game
+fn parse_args() -> Result<Args, String> {
+    let mut parsed = Args::default();
+    let mut args = std::env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--seed" => {
+                let value = args.next().ok_or("Missing value for --seed")?;
+                let seed = value
+                    .parse()
+                    .map_err(|e| format!("Invalid seed {value:?}: {e}"))?;
+                parsed.seed = Some(seed);
+            }
+            "--no-guess" => parsed.no_guess = true,
+            "--replay" => {
+                let value = args.next().ok_or("Missing value for --replay")?;
+                parsed.replay = Some(PathBuf::from(value));
+            }
+            _ => return Err(format!("Unknown argument {arg:?}")),
+        }
+    }
+
+    Ok(parsed)
 }
@@ -0,0 +1,17 @@
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use enimdnal_core::replay::Replay;
+
+/// Directory where replays of finished games are stored.
+pub fn replays_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("enimdnal").join("replays"))
+}
+
+/// Default location for `replay`, inside [replays_dir].
+pub fn default_path(replay: &Replay) -> Option<PathBuf> {
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_secs();
+    let name = format!("{}-{}.json", timestamp, replay.seed());
+
+    replays_dir().map(|dir| dir.join(name))
+}
@@ -0,0 +1,115 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use enimdnal_core::minefield::Board;
+use enimdnal_core::replay::Event;
+
+/// Version of the save file format, bumped on every incompatible change.
+///
+/// Saves of older versions should be migrated in [SavedGame::load],
+/// rather than discarded.
+const VERSION: u32 = 2;
+
+/// Stage the saved game is resumed in.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub enum SavedStage {
+    Playing,
+    Paused,
+}
+
+/// An unfinished game, to be continued later.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedGame {
+    version: u32,
+    pub board: Board,
+    pub run_timer_milisec: u32,
+    pub stage: SavedStage,
+    pub assisted: bool,
+    pub hints_used: u32,
+    pub practice: bool,
+
+    /// Inputs so far, so that the finished game still gets a full replay.
+    pub recording: Vec<Event>,
+}
+
+impl SavedGame {
+    pub fn new(
+        board: Board,
+        run_timer_milisec: u32,
+        stage: SavedStage,
+        assisted: bool,
+        hints_used: u32,
+        practice: bool,
+        recording: Vec<Event>,
+    ) -> Self {
+        Self {
+            version: VERSION,
+            board,
+            run_timer_milisec,
+            stage,
+            assisted,
+            hints_used,
+            practice,
+            recording,
+        }
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let contents = fs::read_to_string(path)?;
+        let mut saved: Value = serde_json::from_str(&contents)?;
+
+        match saved["version"].as_u64() {
+            Some(1) => migrate_v1(&mut saved),
+            Some(version) if version == VERSION as u64 => (),
+            _ => {
+                let message = format!("unsupported save version {}", saved["version"]);
+                return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+            }
+        }
+
+        Ok(serde_json::from_value(saved)?)
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let contents = serde_json::to_string(self)?;
+        fs::write(path, contents)
+    }
+}
+
+/// Bring a version 1 save up to date, where mines and flags were not yet counted,
+/// as every tile held a single mine.
+fn migrate_v1(saved: &mut Value) {
+    let tiles = saved["board"]["tiles"].as_array_mut();
+
+    for tile in tiles.into_iter().flatten() {
+        if tile["object"] == "Mine" {
+            tile["object"] = json!({ "Mine": 1 });
+        }
+        if tile["cover"] == json!({ "Up": "Flag" }) {
+            tile["cover"] = json!({ "Up": { "Flag": 1 } });
+        }
+    }
+
+    saved["version"] = json!(VERSION);
+}
+
+/// Location of the save file, inside the platform's data directory.
+pub fn save_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("enimdnal").join("save.json"))
+}
+
+/// Forget the saved game, if there is one.
+pub fn discard(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,172 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use enimdnal_core::minefield::{stats, Board, Params};
+use enimdnal_core::random::Seed;
+
+/// Version of the scores file format, bumped on every incompatible change.
+const VERSION: u32 = 1;
+
+/// How many of the best times are listed per difficulty.
+pub const TABLE_SIZE: usize = 10;
+
+/// A single won game.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Record {
+    pub time_milisec: u32,
+
+    /// Seconds since the Unix epoch.
+    pub date_secs: u64,
+    pub seed: Seed,
+    pub bbbv: usize,
+    pub clicks: usize,
+
+    /// Runs aided by mine probabilities, hints or practice mode
+    /// are kept, but left out of the tables.
+    pub ranked: bool,
+
+    /// Hints asked for during the run, telling them apart from the other aids.
+    #[serde(default)]
+    pub hints_used: u32,
+}
+
+impl Record {
+    /// Sum up a game won on `board`.
+    pub fn new(
+        board: &Board,
+        time_milisec: u32,
+        clicks: usize,
+        ranked: bool,
+        hints_used: u32,
+    ) -> Self {
+        let date_secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or_default();
+
+        Self {
+            time_milisec,
+            date_secs,
+            seed: board.seed(),
+            bbbv: stats::bbbv(board),
+            clicks,
+            ranked,
+            hints_used,
+        }
+    }
+
+    pub fn efficiency(&self) -> f64 {
+        stats::efficiency(self.bbbv, self.clicks)
+    }
+
+    /// Date of the game as `YYYY-MM-DD`, in UTC.
+    pub fn date(&self) -> String {
+        let (year, month, day) = civil_date(self.date_secs / 86_400);
+
+        format!("{year:04}-{month:02}-{day:02}")
+    }
+}
+
+/// Records of a single difficulty.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Table {
+    params: Params,
+    records: Vec<Record>,
+}
+
+/// Every won game, grouped by difficulty.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Scores {
+    version: u32,
+    tables: Vec<Table>,
+}
+
+impl Default for Scores {
+    fn default() -> Self {
+        Self {
+            version: VERSION,
+            tables: vec![],
+        }
+    }
+}
+
+impl Scores {
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let contents = fs::read_to_string(path)?;
+        let scores: Self = serde_json::from_str(&contents)?;
+
+        if scores.version != VERSION {
+            let message = format!("unsupported scores version {}", scores.version);
+            return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+        }
+
+        Ok(scores)
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let contents = serde_json::to_string(self)?;
+        fs::write(path, contents)
+    }
+
+    pub fn add(&mut self, params: Params, record: Record) {
+        match self.tables.iter_mut().find(|table| table.params == params) {
+            Some(table) => table.records.push(record),
+            None => self.tables.push(Table {
+                params,
+                records: vec![record],
+            }),
+        }
+    }
+
+    /// Fastest ranked games on boards with `params`, at most [TABLE_SIZE] of them.
+    pub fn top(&self, params: Params) -> Vec<&Record> {
+        let Some(table) = self.tables.iter().find(|table| table.params == params) else {
+            return vec![];
+        };
+
+        let mut ranked: Vec<_> = table.records.iter().filter(|r| r.ranked).collect();
+        ranked.sort_by_key(|r| (r.time_milisec, r.date_secs));
+        ranked.truncate(TABLE_SIZE);
+
+        ranked
+    }
+
+    pub fn personal_best(&self, params: Params) -> Option<&Record> {
+        self.top(params).first().copied()
+    }
+}
+
+/// Location of the scores file, inside the platform's data directory.
+pub fn scores_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("enimdnal").join("scores.json"))
+}
+
+/// Convert days since the Unix epoch into a (year, month, day) date.
+///
+/// See <http://howardhinnant.github.io/date_algorithms.html#civil_from_days>.
+fn civil_date(days: u64) -> (u64, u64, u64) {
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = era * 400 + year_of_era + u64::from(month <= 2);
+
+    (year, month, day)
+}
@@ -0,0 +1,244 @@
+use std::fs;
+use std::io;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use enimdnal_core::minefield::{self, MarkCycle, Params};
+
+/// Tile sizes the board can be drawn with, in pixels.
+pub const TILE_SIZES: RangeInclusive<u32> = 20..=80;
+const TILE_SIZE_STEP: u32 = 5;
+
+/// Difficulty preselected in the menu.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default, Serialize, Deserialize)]
+pub enum Difficulty {
+    Beginner,
+    Intermediate,
+    #[default]
+    Expert,
+}
+
+/// Set of colours the game is drawn with.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default, Serialize, Deserialize)]
+pub enum Theme {
+    #[default]
+    Classic,
+    Dark,
+
+    /// Strongly contrasting colours, for readability.
+    Contrast,
+}
+
+/// How hint tiles are explored around.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default, Serialize, Deserialize)]
+pub enum Chording {
+    /// Uncovering an uncovered hint tile explores around it,
+    /// as does the dedicated chord command.
+    #[default]
+    Uncover,
+
+    /// Only the chord command explores, so that a stray click on a hint does nothing.
+    Dedicated,
+
+    /// No exploring at all, every tile is uncovered by hand.
+    Off,
+}
+
+/// What the mine counter of the side panel shows.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default, Serialize, Deserialize)]
+pub enum MineCounter {
+    /// Flags placed out of all the mines.
+    #[default]
+    Flags,
+
+    /// Mines left to flag, going negative when there are too many flags.
+    Remaining,
+}
+
+/// Assistance features available during a game.
+///
+/// Disabled features cannot be turned on, so the runs stay ranked.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Assistance {
+    pub probabilities: bool,
+    pub hints: bool,
+    pub practice: bool,
+}
+
+/// Preferences kept between sessions.
+///
+/// Settings missing from the file keep their defaults.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub difficulty: Difficulty,
+    pub theme: Theme,
+
+    /// Size of a board tile, in pixels.
+    pub tile_size: u32,
+
+    /// Order in which marking a tile cycles through the marks.
+    pub marks: MarkCycle,
+    pub chording: Chording,
+    pub counter: MineCounter,
+    pub assistance: Assistance,
+}
+
+impl Difficulty {
+    pub fn params(self) -> Params {
+        match self {
+            Difficulty::Beginner => minefield::BEGINNER,
+            Difficulty::Intermediate => minefield::INTERMEDIATE,
+            Difficulty::Expert => minefield::EXPERT,
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            Difficulty::Beginner => Difficulty::Intermediate,
+            Difficulty::Intermediate => Difficulty::Expert,
+            Difficulty::Expert => Difficulty::Beginner,
+        }
+    }
+
+    fn previous(self) -> Self {
+        self.next().next()
+    }
+}
+
+impl Theme {
+    fn next(self) -> Self {
+        match self {
+            Theme::Classic => Theme::Dark,
+            Theme::Dark => Theme::Contrast,
+            Theme::Contrast => Theme::Classic,
+        }
+    }
+
+    fn previous(self) -> Self {
+        self.next().next()
+    }
+}
+
+impl Chording {
+    fn next(self) -> Self {
+        match self {
+            Chording::Uncover => Chording::Dedicated,
+            Chording::Dedicated => Chording::Off,
+            Chording::Off => Chording::Uncover,
+        }
+    }
+
+    fn previous(self) -> Self {
+        self.next().next()
+    }
+}
+
+impl Default for Assistance {
+    fn default() -> Self {
+        Self {
+            probabilities: true,
+            hints: true,
+            practice: true,
+        }
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            difficulty: Difficulty::default(),
+            theme: Theme::default(),
+            tile_size: 40,
+            marks: MarkCycle::default(),
+            chording: Chording::default(),
+            counter: MineCounter::default(),
+            assistance: Assistance::default(),
+        }
+    }
+}
+
+impl Settings {
+    /// Read settings from `path`, out of range values are clamped.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let contents = fs::read_to_string(path)?;
+        let mut settings: Self = serde_json::from_str(&contents)?;
+
+        settings.tile_size = settings
+            .tile_size
+            .clamp(*TILE_SIZES.start(), *TILE_SIZES.end());
+
+        Ok(settings)
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let contents = serde_json::to_string_pretty(self)?;
+        fs::write(path, contents)
+    }
+
+    pub fn tile_size(&self) -> f32 {
+        self.tile_size as f32
+    }
+
+    pub fn cycle_difficulty(&mut self, forward: bool) {
+        self.difficulty = match forward {
+            true => self.difficulty.next(),
+            false => self.difficulty.previous(),
+        };
+    }
+
+    pub fn cycle_theme(&mut self, forward: bool) {
+        self.theme = match forward {
+            true => self.theme.next(),
+            false => self.theme.previous(),
+        };
+    }
+
+    pub fn cycle_marks(&mut self, forward: bool) {
+        let next = |marks| match marks {
+            MarkCycle::FlagOnly => MarkCycle::FlagUnsure,
+            MarkCycle::FlagUnsure => MarkCycle::UnsureFirst,
+            MarkCycle::UnsureFirst => MarkCycle::FlagOnly,
+        };
+
+        self.marks = match forward {
+            true => next(self.marks),
+            false => next(next(self.marks)),
+        };
+    }
+
+    pub fn cycle_chording(&mut self, forward: bool) {
+        self.chording = match forward {
+            true => self.chording.next(),
+            false => self.chording.previous(),
+        };
+    }
+
+    pub fn toggle_counter(&mut self) {
+        self.counter = match self.counter {
+            MineCounter::Flags => MineCounter::Remaining,
+            MineCounter::Remaining => MineCounter::Flags,
+        };
+    }
+
+    pub fn adjust_tile_size(&mut self, increase: bool) {
+        let size = match increase {
+            true => self.tile_size + TILE_SIZE_STEP,
+            false => self.tile_size.saturating_sub(TILE_SIZE_STEP),
+        };
+
+        self.tile_size = size.clamp(*TILE_SIZES.start(), *TILE_SIZES.end());
+    }
+}
+
+/// Location of the settings file, inside the platform's config directory.
+pub fn settings_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("enimdnal").join("settings.json"))
+}
@@ -1,62 +1,687 @@
+pub(crate) mod controls;
 pub(crate) mod defeat;
+pub(crate) mod menu;
+pub(crate) mod options;
 mod paused;
 mod playing;
+pub(crate) mod replay;
 mod victory;
 
+use std::collections::HashMap;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
 use notan::draw::*;
+use notan::log;
 use notan::prelude::*;
 
-use crate::drawing::TILE_SIZE;
-use crate::minefield::Board;
+use enimdnal_core::clock::GameClock;
+use enimdnal_core::minefield::solver::{self, Deductions};
+use enimdnal_core::minefield::{self, Board, Cover, Generation, Params};
+use enimdnal_core::random::Seed;
+use enimdnal_core::replay::{self as recording, Action, Event, Replay};
+
+use crate::bindings::{self, Bindings, Command};
+use crate::camera::{Camera, Frame, View};
+use crate::drawing::{self, HINT_BUTTON, MENU_ROW_HEIGHT, MENU_TOP, REPLAY_BAR, UI_WIDTH};
+use crate::replays;
+use crate::save::{self, SavedGame, SavedStage};
+use crate::scores::{self, Record, Scores};
+use crate::settings::{self, Settings};
 
+use controls::ControlsState;
 use defeat::DefeatState;
+use menu::MenuState;
+use options::OptionsState;
+use replay::ReplayState;
 
 #[derive(Debug)]
 pub enum Stage {
+    Menu(MenuState),
+    Controls(ControlsState),
+    Options(OptionsState),
     Playing,
     Paused,
     Victory,
     Defeat(DefeatState),
+    Replay(ReplayState),
+}
+
+/// A tile suggested to the player by the hint action.
+#[derive(Debug, Clone, Copy)]
+pub struct Hint {
+    pub pos: (usize, usize),
+
+    /// Whether the tile is provably safe,
+    /// as opposed to merely being the least risky guess.
+    pub certain: bool,
+
+    /// Whether the player has flagged the tile, even though it is provably safe.
+    pub misflagged: bool,
 }
 
 #[derive(AppState)]
 pub struct State {
     stage: Stage,
     board: Board,
+
+    /// Board placing its mines in the background after the first click,
+    /// for the no-guess search not to hold up the frames.
+    generating: Option<JoinHandle<Board>>,
     hover: Option<(usize, usize)>,
-    run_timer_milisec: u32,
+
+    /// Tile picked with the keyboard, shown once the player starts moving it.
+    cursor: Option<(usize, usize)>,
+    camera: Camera,
+
+    /// Window size as of the current frame.
+    window_size: (f32, f32),
+
+    /// Mouse position as of the previous frame, for dragging the board.
+    last_mouse: (f32, f32),
+
+    /// Time of the run, counted from the first click while the game is being played.
+    clock: GameClock,
+    overlay: bool,
+    probabilities: HashMap<(usize, usize), f64>,
+    assisted: bool,
+    hint: Option<Hint>,
+    hints_used: u32,
+    practice: bool,
+    recording: Vec<Event>,
+    last_replay: Option<Replay>,
+    scores: Scores,
+    saved_game: Option<SavedGame>,
+
+    /// Whether the current run is the one in the saved game,
+    /// either picked up from it or saved since, so the save is over along with the run.
+    continued: bool,
+    bindings: Bindings,
+    settings: Settings,
+
+    /// Seed requested for the next board, instead of a random one.
+    next_seed: Option<Seed>,
     font: Font,
     font_mono: Font,
 }
 
 impl State {
-    pub fn new(font: Font, font_mono: Font) -> Self {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        font: Font,
+        font_mono: Font,
+        scores: Scores,
+        saved_game: Option<SavedGame>,
+        bindings: Bindings,
+        settings: Settings,
+        next_seed: Option<Seed>,
+        no_guess: bool,
+    ) -> Self {
+        let menu = MenuState::new(no_guess, saved_game.is_some(), settings.difficulty);
+
         Self {
-            stage: Stage::Playing,
+            stage: Stage::Menu(menu),
             board: Board::expert(),
+            generating: None,
             hover: None,
-            run_timer_milisec: 0,
+            cursor: None,
+            camera: Camera::default(),
+            window_size: drawing::MENU_SIZE,
+            last_mouse: (0., 0.),
+            clock: GameClock::new(),
+            overlay: false,
+            probabilities: HashMap::new(),
+            assisted: false,
+            hint: None,
+            hints_used: 0,
+            practice: false,
+            recording: vec![],
+            last_replay: None,
+            scores,
+            saved_game,
+            continued: false,
+            bindings,
+            settings,
+            next_seed,
             font,
             font_mono,
         }
     }
 
+    /// Tile under the mouse, as seen through the camera.
+    ///
+    /// Parts of the board hidden behind the side panel cannot be pointed at.
     pub fn mouse_to_board_coords(&self, mouse_x: f32, mouse_y: f32) -> Option<(usize, usize)> {
-        let (width, height) = self.board.dims();
-        let screen_width = width as f32 * TILE_SIZE;
-        let screen_height = height as f32 * TILE_SIZE;
+        if mouse_x >= self.panel_x() {
+            return None;
+        }
+
+        self.view()
+            .screen_to_board(self.board.dims(), (mouse_x, mouse_y))
+    }
+
+    pub fn is_over_hint_button(&self, mouse_x: f32, mouse_y: f32) -> bool {
+        let ((button_x, button_y), (button_width, button_height)) = HINT_BUTTON;
+        let panel_x = mouse_x - self.panel_x();
+
+        let x_in_bounds = panel_x >= button_x && panel_x <= button_x + button_width;
+        let y_in_bounds = mouse_y >= button_y && mouse_y <= button_y + button_height;
+
+        x_in_bounds && y_in_bounds
+    }
+
+    /// Index of the menu entry under the mouse.
+    pub fn menu_entry_at(&self, mouse_x: f32, mouse_y: f32) -> Option<usize> {
+        if mouse_x < 0. || mouse_y < MENU_TOP {
+            return None;
+        }
+
+        let entry = f32::floor((mouse_y - MENU_TOP) / MENU_ROW_HEIGHT) as usize;
+        (entry < menu::ENTRIES.len()).then_some(entry)
+    }
+
+    /// Position along the replay progress bar under the mouse, from 0 to 1.
+    pub fn replay_bar_fraction(&self, mouse_x: f32, mouse_y: f32) -> Option<f32> {
+        let ((bar_x, bar_y), (bar_width, bar_height)) = REPLAY_BAR;
+        let panel_x = mouse_x - self.panel_x();
 
-        let x_in_bounds = mouse_x >= 0. && mouse_x <= screen_width;
-        let y_in_bounds = mouse_y >= 0. && mouse_y <= screen_height;
+        let x_in_bounds = panel_x >= bar_x && panel_x <= bar_x + bar_width;
+        let y_in_bounds = mouse_y >= bar_y && mouse_y <= bar_y + bar_height;
 
         if !x_in_bounds || !y_in_bounds {
             return None;
         }
 
-        let board_x = f32::floor(mouse_x / TILE_SIZE) as usize;
-        let board_y = f32::floor(mouse_y / TILE_SIZE) as usize;
+        Some((panel_x - bar_x) / bar_width)
+    }
+
+    /// Start playing on a new board, sizing the window to fit it.
+    fn start(&mut self, app: &mut App, params: Params) {
+        let seed = self.next_seed.take().unwrap_or_else(Seed::random);
+        self.board = Board::with_seed(params, seed);
+        self.cursor = None;
+        self.continued = false;
+        self.new_game(seed);
+        self.fit_window(app);
+    }
+
+    /// Size the window to show the whole board with the configured tile size,
+    /// as far as the screen allows, and reset the camera.
+    fn fit_window(&mut self, app: &mut App) {
+        let (width, height) = drawing::window_size(self.board.params(), self.settings.tile_size());
+        let (screen_width, screen_height) = app.window().screen_size();
+
+        app.window()
+            .set_size(width.min(screen_width), height.min(screen_height));
+        self.camera = Camera::default();
+    }
+
+    /// Pick up the saved game where it was left off.
+    ///
+    /// Returns whether there was a game to continue.
+    fn continue_game(&mut self, app: &mut App) -> bool {
+        let Some(saved) = self.saved_game.take() else {
+            return false;
+        };
+
+        self.stage = match saved.stage {
+            SavedStage::Playing => Stage::Playing,
+            SavedStage::Paused => Stage::Paused,
+        };
+        self.board = saved.board;
+        self.board.set_mark_cycle(self.settings.marks);
+        self.generating = None;
+        self.cursor = None;
+        self.clock
+            .set_elapsed(Duration::from_millis(saved.run_timer_milisec.into()));
+        if matches!(self.stage, Stage::Playing) {
+            self.clock.start();
+        }
+        self.assisted = saved.assisted;
+        self.overlay = false;
+        self.hint = None;
+        self.hints_used = saved.hints_used;
+        self.practice = saved.practice;
+        self.recording = saved.recording;
+        self.continued = true;
+        self.refresh_probabilities();
+        self.fit_window(app);
+
+        true
+    }
+
+    /// Write the game in progress to disk, to be continued later.
+    ///
+    /// Games that are over, or have not started yet, are not saved.
+    fn save_game(&mut self) {
+        let stage = match self.stage {
+            Stage::Playing => SavedStage::Playing,
+            Stage::Paused => SavedStage::Paused,
+            _ => return,
+        };
+
+        if !self.board.is_initialized() {
+            return;
+        }
+
+        let saved = SavedGame::new(
+            self.board.clone(),
+            self.clock.elapsed_milisec(),
+            stage,
+            self.assisted,
+            self.hints_used,
+            self.practice,
+            self.recording.clone(),
+        );
+
+        match save::save_path() {
+            Some(path) => {
+                if let Err(e) = saved.save(&path) {
+                    log::warn!("Failed to save the game to {}: {}", path.display(), e);
+                }
+            }
+            None => log::warn!("No data directory to save the game in"),
+        }
+
+        self.saved_game = Some(saved);
+        self.continued = true;
+    }
+
+    /// Leave the current board for the difficulty menu.
+    ///
+    /// A game left unfinished can be continued from there.
+    fn open_menu(&mut self, app: &mut App) {
+        self.save_game();
+        self.clock.pause();
+
+        let no_guess = self.board.params().generation() == minefield::Generation::NoGuess;
+        let mut menu = MenuState::new(
+            no_guess,
+            self.saved_game.is_some(),
+            self.settings.difficulty,
+        );
+        menu.topology = self.board.params().topology();
+        menu.wrapping = self.board.params().wrapping();
+        menu.neighborhood = self.board.params().neighborhood();
+        menu.distribution = self.board.params().distribution();
+        self.stage = Stage::Menu(menu);
+        self.hover = None;
+
+        let (width, height) = drawing::MENU_SIZE;
+        app.window().set_size(width as _, height as _);
+    }
+
+    fn open_controls(&mut self) {
+        self.stage = Stage::Controls(ControlsState::default());
+    }
+
+    fn open_options(&mut self) {
+        self.stage = Stage::Options(OptionsState::default());
+    }
+
+    fn save_settings(&self) {
+        match settings::settings_path() {
+            Some(path) => {
+                if let Err(e) = self.settings.save(&path) {
+                    log::warn!("Failed to save settings to {}: {}", path.display(), e);
+                }
+            }
+            None => log::warn!("No config directory to save settings in"),
+        }
+    }
+
+    fn save_bindings(&self) {
+        match bindings::bindings_path() {
+            Some(path) => {
+                if let Err(e) = self.bindings.save(&path) {
+                    log::warn!("Failed to save bindings to {}: {}", path.display(), e);
+                }
+            }
+            None => log::warn!("No config directory to save bindings in"),
+        }
+    }
+
+    /// Abandon the current run and start over on a board generated from `seed`.
+    fn new_game(&mut self, seed: Seed) {
+        self.stage = Stage::Playing;
+        self.clock.reset();
+        self.generating = None;
+        self.board.reset(seed);
+        self.board.set_mark_cycle(self.settings.marks);
+        self.assisted = false;
+        self.overlay = false;
+        self.hint = None;
+        self.hints_used = 0;
+        self.practice = false;
+        self.recording.clear();
+        self.refresh_probabilities();
+    }
+
+    /// Move the keyboard cursor one tile in the given direction.
+    ///
+    /// With `to_edge` it goes all the way to the board edge,
+    /// with `skip_uncovered` on to the next covered tile, staying put if there is none.
+    fn move_cursor(&mut self, (dx, dy): (isize, isize), to_edge: bool, skip_uncovered: bool) {
+        let (width, height) = self.board.dims();
+        let Some(start) = self.cursor else {
+            self.cursor = Some((width / 2, height / 2));
+            return;
+        };
+
+        let step = |(x, y): (usize, usize)| {
+            let next = (x.checked_add_signed(dx)?, y.checked_add_signed(dy)?);
+            (next.0 < width && next.1 < height).then_some(next)
+        };
+        let mut ahead = std::iter::successors(Some(start), |&pos| step(pos)).skip(1);
+
+        let target = if skip_uncovered {
+            ahead.find(|&(x, y)| matches!(self.board.tile(x, y).cover(), Cover::Up(_)))
+        } else if to_edge {
+            ahead.last()
+        } else {
+            ahead.next()
+        };
+
+        if let Some(target) = target {
+            self.cursor = Some(target);
+        }
+    }
+
+    /// Zoom with the mouse wheel, and drag the board while the pan binding is held.
+    fn update_camera(&mut self, app: &App) {
+        let frame = self.frame();
+        let mouse @ (mouse_x, mouse_y) = app.mouse.position();
+        let wheel = app.mouse.wheel_delta.y;
+
+        if wheel != 0. && mouse_x < self.panel_x() {
+            self.camera.zoom_at(frame, mouse, wheel);
+        }
+
+        if self.bindings.held(Command::Pan, app) {
+            let (last_x, last_y) = self.last_mouse;
+            self.camera
+                .pan_by(frame, (mouse_x - last_x, mouse_y - last_y));
+        }
 
-        Some((board_x, board_y))
+        self.camera.clamp(frame);
+        self.last_mouse = mouse;
+    }
+
+    /// Act on the board on behalf of the player, recording the input.
+    ///
+    /// The first click on a no-guess board is handled in the background,
+    /// see [State::poll_generation].
+    fn act(&mut self, action: Action) {
+        self.record(action);
+
+        let no_guess = self.board.params().generation() == Generation::NoGuess;
+
+        match action {
+            Action::Primary(x, y) if no_guess && !self.board.is_initialized() => {
+                let mut board = self.board.clone();
+                self.generating = Some(thread::spawn(move || {
+                    board.handle_primary_action(x, y);
+                    board
+                }));
+            }
+            _ => {
+                recording::apply(&mut self.board, action);
+                self.acted();
+            }
+        }
+    }
+
+    /// Take the board generated in the background once it is ready.
+    ///
+    /// Returns whether the board can be played on,
+    /// as it cannot while still being generated.
+    fn poll_generation(&mut self) -> bool {
+        match &self.generating {
+            None => return true,
+            Some(generating) if !generating.is_finished() => return false,
+            Some(_) => (),
+        }
+
+        if let Some(generating) = self.generating.take() {
+            self.board = generating.join().expect("Board generation panicked");
+            self.acted();
+        }
+
+        true
+    }
+
+    /// Update the rest of the game after acting on the board.
+    fn acted(&mut self) {
+        self.board_changed();
+
+        // the first click places the mines and starts the clock
+        if self.board.is_initialized() {
+            self.clock.start();
+        }
+    }
+
+    fn pause_game(&mut self) {
+        self.clock.pause();
+        self.stage = Stage::Paused;
+    }
+
+    /// Get back to playing, after a pause or a taken back fatal click.
+    fn resume_game(&mut self) {
+        self.stage = Stage::Playing;
+        if self.board.is_initialized() {
+            self.clock.start();
+        }
+    }
+
+    fn record(&mut self, action: Action) {
+        let event = Event {
+            time_milisec: self.clock.elapsed_milisec(),
+            action,
+        };
+        self.recording.push(event);
+    }
+
+    /// Store the replay of a run that has just ended.
+    fn finish_run(&mut self) {
+        self.clock.pause();
+        let replay = Replay::new(
+            &self.board,
+            self.recording.clone(),
+            self.clock.elapsed_milisec(),
+            self.hints_used,
+        );
+
+        match replays::default_path(&replay) {
+            Some(path) => {
+                if let Err(e) = replay.save(&path) {
+                    log::warn!("Failed to save replay to {}: {}", path.display(), e);
+                }
+            }
+            None => log::warn!("No data directory to save replays in"),
+        }
+
+        self.last_replay = Some(replay);
+
+        // the saved game, if that is what was being played, is over now,
+        // while one left behind before starting another board is kept
+        if !self.continued {
+            return;
+        }
+
+        self.continued = false;
+        self.saved_game = None;
+        if let Some(path) = save::save_path() {
+            if let Err(e) = save::discard(&path) {
+                log::warn!("Failed to remove the saved game {}: {}", path.display(), e);
+            }
+        }
+    }
+
+    /// Add the just won run to the scores and write them to disk.
+    fn record_score(&mut self) {
+        let clicks = self.board.clicks().total() as usize;
+        let time = self.clock.elapsed_milisec();
+
+        // the overlay marks the run as assisted as soon as it is shown
+        debug_assert!(self.assisted || !self.overlay);
+        let ranked = !self.assisted && !self.overlay;
+        let record = Record::new(&self.board, time, clicks, ranked, self.hints_used);
+        self.scores.add(self.board.params(), record);
+
+        match scores::scores_path() {
+            Some(path) => {
+                if let Err(e) = self.scores.save(&path) {
+                    log::warn!("Failed to save scores to {}: {}", path.display(), e);
+                }
+            }
+            None => log::warn!("No data directory to save scores in"),
+        }
+    }
+
+    /// Switch to watching a recorded game.
+    fn watch(&mut self, replay: Replay) {
+        let mut final_board = replay.board();
+        for event in replay.events() {
+            recording::apply(&mut final_board, event.action);
+        }
+        if !replay.matches_layout(&final_board) {
+            log::warn!("Replay layout differs from the recorded one, playback may be inaccurate");
+        }
+
+        let mut viewer = ReplayState::new(replay);
+        viewer.seek(&mut self.board, 0.);
+
+        self.stage = Stage::Replay(viewer);
+        self.hint = None;
+        self.probabilities.clear();
+    }
+
+    /// Update everything derived from the board after acting on it.
+    fn board_changed(&mut self) {
+        self.hint = None;
+        self.refresh_probabilities();
+    }
+
+    /// Practice mode unlocks undo and redo,
+    /// at the cost of the run no longer qualifying for scores.
+    ///
+    /// It can always be turned off, but only turned on if enabled in the settings.
+    fn toggle_practice(&mut self) {
+        if !self.practice && !self.settings.assistance.practice {
+            return;
+        }
+
+        self.practice = !self.practice;
+        self.assisted |= self.practice;
+    }
+
+    /// Returns whether an action was actually undone.
+    fn undo(&mut self) -> bool {
+        let undone = self.practice && self.board.undo();
+        if undone {
+            self.record(Action::Undo);
+            self.board_changed();
+        }
+
+        undone
+    }
+
+    fn redo(&mut self) {
+        if self.practice && self.board.redo() {
+            self.record(Action::Redo);
+            self.board_changed();
+        }
+    }
+
+    /// Point the player to a covered tile that is safe to uncover,
+    /// or the least risky one when no tile is provably safe.
+    ///
+    /// A safe tile the player has flagged counts too, pointing out the misplaced flag.
+    fn request_hint(&mut self) {
+        if !self.settings.assistance.hints {
+            return;
+        }
+
+        self.hint = self.find_hint();
+
+        if self.hint.is_some() {
+            self.hints_used += 1;
+            self.assisted = true;
+        }
+    }
+
+    fn find_hint(&self) -> Option<Hint> {
+        let uncoverable = |&(x, y): &(usize, usize)| self.board.tile(x, y).is_uncoverable();
+
+        if !self.board.is_initialized() {
+            // the first uncovered tile never holds a mine
+            let (width, height) = self.board.dims();
+            let pos = (width / 2, height / 2);
+            return Some(Hint {
+                pos,
+                certain: true,
+                misflagged: false,
+            });
+        }
+
+        // the solver ignores the marks, so a safe tile may be under a misplaced flag,
+        // which is still a better hint than any guess
+        let Deductions { safe, .. } = solver::solve(&self.board);
+        let safe_tile = |misflagged| {
+            let pos = safe
+                .iter()
+                .copied()
+                .filter(|&(x, y)| self.board.tile(x, y).is_flag() == misflagged)
+                .min()?;
+
+            Some(Hint {
+                pos,
+                certain: true,
+                misflagged,
+            })
+        };
+        if let Some(hint) = safe_tile(false).or_else(|| safe_tile(true)) {
+            return Some(hint);
+        }
+
+        solver::probabilities(&self.board)
+            .into_iter()
+            .filter(|(pos, _)| uncoverable(pos))
+            .min_by(|(a_pos, a), (b_pos, b)| a.total_cmp(b).then(a_pos.cmp(b_pos)))
+            .map(|(pos, _)| Hint {
+                pos,
+                certain: false,
+                misflagged: false,
+            })
+    }
+
+    /// Show or hide the mine probability overlay.
+    ///
+    /// Showing it counts as assistance, so the run no longer qualifies for scores.
+    /// It can always be hidden, but only shown if enabled in the settings.
+    fn toggle_overlay(&mut self) {
+        if !self.overlay && !self.settings.assistance.probabilities {
+            return;
+        }
+
+        self.overlay = !self.overlay;
+        self.assisted |= self.overlay;
+        self.refresh_probabilities();
+    }
+
+    /// Hide the overlay for good, once it has been disabled in the settings.
+    fn hide_overlay(&mut self) {
+        self.overlay = false;
+        self.refresh_probabilities();
+    }
+
+    /// Recompute the overlay after the visible state of the board has changed.
+    fn refresh_probabilities(&mut self) {
+        self.probabilities = if self.overlay {
+            solver::probabilities(&self.board)
+        } else {
+            HashMap::new()
+        };
     }
 
     pub fn stage(&self) -> &Stage {
@@ -67,12 +692,83 @@ impl State {
         &self.board
     }
 
+    /// Whether the board is still placing its mines after the first click.
+    pub fn is_generating(&self) -> bool {
+        self.generating.is_some()
+    }
+
     pub fn hover_index(&self) -> Option<(usize, usize)> {
         self.hover
     }
 
+    pub fn cursor(&self) -> Option<(usize, usize)> {
+        self.cursor
+    }
+
+    /// Window area left for the board, next to the side panel.
+    pub fn frame(&self) -> Frame {
+        let (_, height) = self.window_size;
+
+        Frame {
+            viewport: (f32::max(self.panel_x(), 1.), f32::max(height, 1.)),
+            dims: self.board.dims(),
+            topology: self.board.params().topology(),
+            wrapping: self.board.params().wrapping(),
+            max_tile_size: self.settings.tile_size(),
+        }
+    }
+
+    pub fn view(&self) -> View {
+        self.camera.view(self.frame())
+    }
+
+    /// Left edge of the side panel, which sticks to the right edge of the window.
+    pub fn panel_x(&self) -> f32 {
+        let (width, _) = self.window_size;
+        f32::max(width - UI_WIDTH, 0.)
+    }
+
     pub fn run_timer_milisec(&self) -> u32 {
-        self.run_timer_milisec
+        self.clock.elapsed_milisec()
+    }
+
+    /// Chance of a covered tile holding a mine, if the overlay is shown.
+    pub fn probability(&self, x: usize, y: usize) -> Option<f64> {
+        self.probabilities.get(&(x, y)).copied()
+    }
+
+    pub fn hint(&self) -> Option<Hint> {
+        self.hint
+    }
+
+    pub fn hints_used(&self) -> u32 {
+        self.hints_used
+    }
+
+    pub fn is_practice(&self) -> bool {
+        self.practice
+    }
+
+    pub fn scores(&self) -> &Scores {
+        &self.scores
+    }
+
+    pub fn saved_game(&self) -> Option<&SavedGame> {
+        self.saved_game.as_ref()
+    }
+
+    pub fn bindings(&self) -> &Bindings {
+        &self.bindings
+    }
+
+    pub fn settings(&self) -> &Settings {
+        &self.settings
+    }
+
+    /// Whether the current run used any assistance,
+    /// which disqualifies it from score keeping.
+    pub fn is_assisted(&self) -> bool {
+        self.assisted
     }
 
     pub fn font(&self) -> &Font {
@@ -84,7 +780,13 @@ impl State {
     }
 }
 
-pub fn setup(gfx: &mut Graphics) -> State {
+pub fn setup(
+    app: &mut App,
+    gfx: &mut Graphics,
+    seed: Option<Seed>,
+    no_guess: bool,
+    replay: Option<Replay>,
+) -> State {
     let font = gfx
         .create_font(include_bytes!("../assets/OpenSauceTwo-Bold.ttf"))
         .unwrap();
@@ -94,17 +796,99 @@ pub fn setup(gfx: &mut Graphics) -> State {
         ))
         .unwrap();
 
-    State::new(font, font_mono)
+    let scores = match scores::scores_path() {
+        Some(path) => match Scores::load(&path) {
+            Ok(scores) => scores,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Scores::default(),
+            Err(e) => {
+                log::warn!("Failed to load scores from {}: {}", path.display(), e);
+                Scores::default()
+            }
+        },
+        None => Scores::default(),
+    };
+
+    let saved_game = match save::save_path() {
+        Some(path) => match SavedGame::load(&path) {
+            Ok(saved) => Some(saved),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => {
+                log::warn!("Failed to load the saved game {}: {}", path.display(), e);
+                None
+            }
+        },
+        None => None,
+    };
+
+    let bindings = match bindings::bindings_path() {
+        Some(path) => match Bindings::load(&path) {
+            Ok(bindings) => bindings,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Bindings::default(),
+            Err(e) => {
+                log::warn!("Failed to load bindings from {}: {}", path.display(), e);
+                Bindings::default()
+            }
+        },
+        None => Bindings::default(),
+    };
+    if bindings.has_conflicts() {
+        log::warn!("Some bindings conflict with each other, fix them in the controls menu");
+    }
+
+    let settings = match settings::settings_path() {
+        Some(path) => match Settings::load(&path) {
+            Ok(settings) => settings,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Settings::default(),
+            Err(e) => {
+                log::warn!("Failed to load settings from {}: {}", path.display(), e);
+                Settings::default()
+            }
+        },
+        None => Settings::default(),
+    };
+
+    let mut state = State::new(
+        font, font_mono, scores, saved_game, bindings, settings, seed, no_guess,
+    );
+    if let Some(replay) = replay {
+        state.board = replay.board();
+        state.watch(replay);
+        state.fit_window(app);
+    }
+
+    state
+}
+
+pub fn event(state: &mut State, event: notan::Event) {
+    if let notan::Event::Exit = event {
+        state.save_game();
+    }
 }
 
 pub fn update(app: &mut App, state: &mut State) {
+    let (width, height) = app.window().size();
+    state.window_size = (width as f32, height as f32);
+
+    if !matches!(
+        state.stage,
+        Stage::Menu(_) | Stage::Controls(_) | Stage::Options(_)
+    ) {
+        state.update_camera(app);
+    }
+
+    if app.keyboard.was_pressed(KeyCode::C) {
+        let seed = state.board.seed().to_string();
+        app.backend.set_clipboard_text(&seed);
+    }
+
     match &mut state.stage {
+        Stage::Menu(_) => menu::update(app, state),
+        Stage::Controls(_) => controls::update(app, state),
+        Stage::Options(_) => options::update(app, state),
         Stage::Playing => playing::update(app, state),
         Stage::Paused => paused::update(app, state),
-        Stage::Defeat(defeat_state) => {
-            defeat_state.update(app);
-            defeat::update(app, state);
-        }
+        Stage::Defeat(_) => defeat::update(app, state),
         Stage::Victory => victory::update(app, state),
+        Stage::Replay(_) => replay::update(app, state),
     }
 }
@@ -0,0 +1,121 @@
+use notan::prelude::*;
+
+use crate::bindings::{Trigger, COMMANDS};
+use crate::state::{Stage, State};
+
+const MOUSE_BUTTONS: [MouseButton; 3] =
+    [MouseButton::Left, MouseButton::Right, MouseButton::Middle];
+
+/// Keys that only ever act as modifiers, and cannot be bound on their own.
+const MODIFIERS: [KeyCode; 8] = [
+    KeyCode::LControl,
+    KeyCode::RControl,
+    KeyCode::LShift,
+    KeyCode::RShift,
+    KeyCode::LAlt,
+    KeyCode::RAlt,
+    KeyCode::LWin,
+    KeyCode::RWin,
+];
+
+/// Screen for rebinding the [bindings::Command]s.
+#[derive(Debug, Default)]
+pub struct ControlsState {
+    pub selected: usize,
+
+    /// Whether the next input becomes the binding of the selected command.
+    pub capturing: bool,
+
+    /// Mouse buttons pressed since capturing started,
+    /// the binding is made once one of them is released.
+    held: Vec<MouseButton>,
+
+    /// Why the controls could not be saved.
+    pub error: Option<String>,
+}
+
+impl ControlsState {
+    /// Wait for the input to bind to the selected command.
+    ///
+    /// Returns the trigger once the input is complete.
+    fn capture(&mut self, app: &App) -> Option<Trigger> {
+        let keyboard = &app.keyboard;
+        let key = keyboard
+            .pressed
+            .iter()
+            .copied()
+            .find(|key| !MODIFIERS.contains(key));
+
+        if let Some(key) = key {
+            return match keyboard.ctrl() {
+                true => Some(Trigger::Ctrl(key)),
+                false => Some(Trigger::Key(key)),
+            };
+        }
+
+        for button in MOUSE_BUTTONS {
+            if app.mouse.was_pressed(button) && !self.held.contains(&button) {
+                self.held.push(button);
+            }
+        }
+
+        let released = MOUSE_BUTTONS
+            .iter()
+            .any(|&button| app.mouse.was_released(button));
+        if !released || self.held.is_empty() {
+            return None;
+        }
+
+        let together =
+            self.held.contains(&MouseButton::Left) && self.held.contains(&MouseButton::Right);
+        let trigger = match together {
+            true => Trigger::LeftRight,
+            false => Trigger::Mouse(self.held[0]),
+        };
+        self.held.clear();
+
+        Some(trigger)
+    }
+}
+
+pub fn update(app: &mut App, state: &mut State) {
+    let Stage::Controls(controls) = &mut state.stage else {
+        return;
+    };
+
+    let command = COMMANDS[controls.selected];
+
+    if controls.capturing {
+        if app.keyboard.was_pressed(KeyCode::Escape) {
+            controls.capturing = false;
+        } else if let Some(trigger) = controls.capture(app) {
+            controls.capturing = false;
+            state.bindings.set(command, trigger);
+        }
+        return;
+    }
+
+    let keyboard = &app.keyboard;
+
+    if keyboard.was_pressed(KeyCode::Up) {
+        controls.selected = controls
+            .selected
+            .checked_sub(1)
+            .unwrap_or(COMMANDS.len() - 1);
+    } else if keyboard.was_pressed(KeyCode::Down) {
+        controls.selected = (controls.selected + 1) % COMMANDS.len();
+    } else if keyboard.was_pressed(KeyCode::Return) {
+        controls.capturing = true;
+        controls.held.clear();
+    } else if keyboard.was_pressed(KeyCode::Back) {
+        state.bindings.reset(command);
+    } else if keyboard.was_pressed(KeyCode::Escape) {
+        if state.bindings.has_conflicts() {
+            controls.error = Some("Resolve the conflicts first".to_string());
+            return;
+        }
+
+        state.save_bindings();
+        state.open_menu(app);
+    }
+}
@@ -1,6 +1,10 @@
 use notan::prelude::*;
 
-use crate::state::{Stage, State};
+use enimdnal_core::clock::GameClock;
+use enimdnal_core::random::Seed;
+
+use crate::bindings::Command;
+use crate::state::State;
 
 #[derive(Debug)]
 pub struct Explosion {
@@ -11,22 +15,40 @@ pub struct Explosion {
 #[derive(Debug)]
 pub struct DefeatState {
     pub explosions: Vec<Explosion>,
-    pub elapsed_milisec: u32,
+
+    /// Time since the fatal click, which the explosions are timed against.
+    clock: GameClock,
 }
 
 impl DefeatState {
-    pub fn update(&mut self, app: &App) {
-        let delta = app.timer.delta().subsec_millis();
-        self.elapsed_milisec += delta;
+    pub fn new(explosions: Vec<Explosion>) -> Self {
+        let mut clock = GameClock::new();
+        clock.start();
+
+        Self { explosions, clock }
+    }
+
+    pub fn elapsed_milisec(&self) -> u32 {
+        self.clock.elapsed_milisec()
     }
 }
 
 pub fn update(app: &mut App, state: &mut State) {
     state.hover = None;
 
-    if app.keyboard.was_pressed(KeyCode::Space) {
-        state.stage = Stage::Playing;
-        state.run_timer_milisec = 0;
-        state.board.reset();
+    if state.bindings.triggered(Command::Restart, app) {
+        state.new_game(Seed::random());
+    } else if app.keyboard.was_pressed(KeyCode::Back) {
+        let seed = state.board.seed();
+        state.new_game(seed);
+    } else if app.keyboard.was_pressed(KeyCode::R) {
+        if let Some(replay) = state.last_replay.clone() {
+            state.watch(replay);
+        }
+    } else if app.keyboard.was_pressed(KeyCode::Escape) {
+        state.open_menu(app);
+    } else if state.bindings.triggered(Command::Undo, app) && state.undo() {
+        // practice runs may take back the fatal click
+        state.resume_game();
     }
 }
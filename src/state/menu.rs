@@ -0,0 +1,341 @@
+use notan::prelude::*;
+
+use enimdnal_core::minefield::topology::NEIGHBORHOOD_PRESETS;
+use enimdnal_core::minefield::{
+    self, Distribution, Generation, Neighborhood, Params, ParamsError, Topology, MAX_STACK,
+};
+
+use crate::settings::Difficulty;
+use crate::state::{Stage, State};
+
+/// Largest value that can be typed into a custom board field.
+const MAX_FIELD_VALUE: usize = 99_999;
+
+/// A single line of the menu.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Entry {
+    Continue,
+    Beginner,
+    Intermediate,
+    Expert,
+    Custom,
+    Width,
+    Height,
+    Mines,
+    NoGuess,
+    Tiles,
+    Wrapping,
+    Neighbors,
+    Stacking,
+    Options,
+    Controls,
+}
+
+pub const ENTRIES: [Entry; 15] = [
+    Entry::Continue,
+    Entry::Beginner,
+    Entry::Intermediate,
+    Entry::Expert,
+    Entry::Custom,
+    Entry::Width,
+    Entry::Height,
+    Entry::Mines,
+    Entry::NoGuess,
+    Entry::Tiles,
+    Entry::Wrapping,
+    Entry::Neighbors,
+    Entry::Stacking,
+    Entry::Options,
+    Entry::Controls,
+];
+
+#[derive(Debug)]
+pub struct MenuState {
+    pub selected: usize,
+    pub no_guess: bool,
+    pub topology: Topology,
+    pub wrapping: bool,
+    pub neighborhood: Neighborhood,
+    pub distribution: Distribution,
+
+    /// Custom board settings, as typed in.
+    pub width: usize,
+    pub height: usize,
+    pub mines: usize,
+
+    /// Why the custom board could not be started.
+    pub error: Option<String>,
+}
+
+impl MenuState {
+    /// Menu with the saved game preselected, if there is one,
+    /// or the `difficulty` otherwise, which also fills in the custom board.
+    pub fn new(no_guess: bool, can_continue: bool, difficulty: Difficulty) -> Self {
+        let preselected = match (can_continue, difficulty) {
+            (true, _) => Entry::Continue,
+            (false, Difficulty::Beginner) => Entry::Beginner,
+            (false, Difficulty::Intermediate) => Entry::Intermediate,
+            (false, Difficulty::Expert) => Entry::Expert,
+        };
+        let params = difficulty.params();
+
+        Self {
+            selected: ENTRIES
+                .iter()
+                .position(|&entry| entry == preselected)
+                .unwrap_or_default(),
+            no_guess,
+            topology: Topology::Square,
+            wrapping: false,
+            neighborhood: Neighborhood::Adjacent,
+            distribution: Distribution::Single,
+            width: params.width(),
+            height: params.height(),
+            mines: params.mines(),
+            error: None,
+        }
+    }
+
+    pub fn entry(&self) -> Entry {
+        ENTRIES[self.selected]
+    }
+
+    /// Board parameters chosen by the currently selected entry.
+    fn chosen_params(&self) -> Result<Params, ParamsError> {
+        let (size, mines) = match self.entry() {
+            Entry::Continue => unreachable!("the saved game comes with its own params"),
+            Entry::Options | Entry::Controls => unreachable!("these screens start no game"),
+            Entry::Beginner => (minefield::BEGINNER, minefield::BEGINNER.mines()),
+            Entry::Intermediate => (minefield::INTERMEDIATE, minefield::INTERMEDIATE.mines()),
+            Entry::Expert => (minefield::EXPERT, minefield::EXPERT.mines()),
+            Entry::Custom
+            | Entry::Width
+            | Entry::Height
+            | Entry::Mines
+            | Entry::NoGuess
+            | Entry::Tiles
+            | Entry::Wrapping
+            | Entry::Neighbors
+            | Entry::Stacking => (Params::new(self.width, self.height, 0)?, self.mines),
+        };
+
+        let generation = if self.no_guess {
+            Generation::NoGuess
+        } else {
+            Generation::Random
+        };
+
+        // the mines are put back last, as how many of them fit depends on the rest
+        size.with_mines(0)?
+            .with_generation(generation)
+            .with_topology(self.topology)?
+            .with_wrapping(self.wrapping)?
+            .with_neighborhood(self.neighborhood)?
+            .with_distribution(self.distribution)?
+            .with_mines(mines)
+    }
+
+    fn field(&mut self) -> Option<&mut usize> {
+        match self.entry() {
+            Entry::Width => Some(&mut self.width),
+            Entry::Height => Some(&mut self.height),
+            Entry::Mines => Some(&mut self.mines),
+            _ => None,
+        }
+    }
+
+    fn toggle_topology(&mut self) {
+        self.topology = match self.topology {
+            Topology::Square => Topology::Hex,
+            Topology::Hex => Topology::Square,
+        };
+    }
+
+    /// Switch to the next or previous preset neighborhood,
+    /// a custom one going over to the first preset.
+    fn cycle_neighborhood(&mut self, forward: bool) {
+        let count = NEIGHBORHOOD_PRESETS.len();
+        let next = match NEIGHBORHOOD_PRESETS
+            .iter()
+            .position(|&n| n == self.neighborhood)
+        {
+            Some(i) if forward => (i + 1) % count,
+            Some(i) => (i + count - 1) % count,
+            None => 0,
+        };
+
+        self.neighborhood = NEIGHBORHOOD_PRESETS[next];
+    }
+
+    /// Switch to one more or one less mine per tile, going around after [MAX_STACK].
+    fn cycle_distribution(&mut self, forward: bool) {
+        let stack = self.distribution.stack();
+        let next = match forward {
+            true => stack % MAX_STACK + 1,
+            false => (stack + MAX_STACK - 2) % MAX_STACK + 1,
+        };
+
+        self.distribution = match next {
+            1 => Distribution::Single,
+            stack => Distribution::Stacked(stack),
+        };
+    }
+
+    fn adjust(&mut self, increase: bool) {
+        if self.entry() == Entry::NoGuess {
+            self.no_guess = !self.no_guess;
+            return;
+        }
+
+        if self.entry() == Entry::Tiles {
+            self.toggle_topology();
+            return;
+        }
+
+        if self.entry() == Entry::Wrapping {
+            self.wrapping = !self.wrapping;
+            return;
+        }
+
+        if self.entry() == Entry::Neighbors {
+            self.cycle_neighborhood(increase);
+            return;
+        }
+
+        if self.entry() == Entry::Stacking {
+            self.cycle_distribution(increase);
+            return;
+        }
+
+        if let Some(value) = self.field() {
+            *value = match increase {
+                true => usize::min(*value + 1, MAX_FIELD_VALUE),
+                false => value.saturating_sub(1),
+            };
+        }
+    }
+
+    fn type_digit(&mut self, digit: usize) {
+        if let Some(value) = self.field() {
+            let typed = *value * 10 + digit;
+            if typed <= MAX_FIELD_VALUE {
+                *value = typed;
+            }
+        }
+    }
+
+    fn erase_digit(&mut self) {
+        if let Some(value) = self.field() {
+            *value /= 10;
+        }
+    }
+}
+
+pub fn update(app: &mut App, state: &mut State) {
+    let (mouse_x, mouse_y) = app.mouse.position();
+    let hovered = state.menu_entry_at(mouse_x, mouse_y);
+
+    let Stage::Menu(menu) = &mut state.stage else {
+        return;
+    };
+
+    let keyboard = &app.keyboard;
+    let mut confirmed = keyboard.was_pressed(KeyCode::Return);
+
+    if let Some(entry) = hovered {
+        if app.mouse.left_was_pressed() {
+            menu.selected = entry;
+            confirmed = true;
+        }
+    }
+
+    if keyboard.was_pressed(KeyCode::Up) {
+        menu.selected = menu.selected.checked_sub(1).unwrap_or(ENTRIES.len() - 1);
+    } else if keyboard.was_pressed(KeyCode::Down) {
+        menu.selected = (menu.selected + 1) % ENTRIES.len();
+    } else if keyboard.was_pressed(KeyCode::Left) {
+        menu.adjust(false);
+    } else if keyboard.was_pressed(KeyCode::Right) {
+        menu.adjust(true);
+    } else if keyboard.was_pressed(KeyCode::Back) {
+        menu.erase_digit();
+    }
+
+    if let Some(digit) = typed_digit(keyboard) {
+        menu.type_digit(digit);
+    }
+
+    if !confirmed {
+        return;
+    }
+
+    menu.error = None;
+
+    if menu.entry() == Entry::NoGuess {
+        menu.no_guess = !menu.no_guess;
+        return;
+    }
+
+    if menu.entry() == Entry::Tiles {
+        menu.toggle_topology();
+        return;
+    }
+
+    if menu.entry() == Entry::Wrapping {
+        menu.wrapping = !menu.wrapping;
+        return;
+    }
+
+    if menu.entry() == Entry::Neighbors {
+        menu.cycle_neighborhood(true);
+        return;
+    }
+
+    if menu.entry() == Entry::Stacking {
+        menu.cycle_distribution(true);
+        return;
+    }
+
+    if menu.entry() == Entry::Options {
+        state.open_options();
+        return;
+    }
+
+    if menu.entry() == Entry::Controls {
+        state.open_controls();
+        return;
+    }
+
+    if menu.entry() == Entry::Continue {
+        if !state.continue_game(app) {
+            if let Stage::Menu(menu) = &mut state.stage {
+                menu.error = Some("No saved game to continue".to_string());
+            }
+        }
+        return;
+    }
+
+    match menu.chosen_params() {
+        Ok(params) => state.start(app, params),
+        Err(e) => menu.error = Some(e.to_string()),
+    }
+}
+
+fn typed_digit(keyboard: &Keyboard) -> Option<usize> {
+    const DIGITS: [(KeyCode, KeyCode); 10] = [
+        (KeyCode::Key0, KeyCode::Numpad0),
+        (KeyCode::Key1, KeyCode::Numpad1),
+        (KeyCode::Key2, KeyCode::Numpad2),
+        (KeyCode::Key3, KeyCode::Numpad3),
+        (KeyCode::Key4, KeyCode::Numpad4),
+        (KeyCode::Key5, KeyCode::Numpad5),
+        (KeyCode::Key6, KeyCode::Numpad6),
+        (KeyCode::Key7, KeyCode::Numpad7),
+        (KeyCode::Key8, KeyCode::Numpad8),
+        (KeyCode::Key9, KeyCode::Numpad9),
+    ];
+
+    DIGITS
+        .iter()
+        .position(|&(key, numpad)| keyboard.was_pressed(key) || keyboard.was_pressed(numpad))
+}
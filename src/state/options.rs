@@ -0,0 +1,89 @@
+use notan::prelude::*;
+
+use crate::settings::Settings;
+use crate::state::{Stage, State};
+
+/// A single line of the options screen.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Setting {
+    Difficulty,
+    Theme,
+    TileSize,
+    Marks,
+    Chording,
+    Counter,
+    Probabilities,
+    Hints,
+    Practice,
+}
+
+pub const SETTINGS: [Setting; 9] = [
+    Setting::Difficulty,
+    Setting::Theme,
+    Setting::TileSize,
+    Setting::Marks,
+    Setting::Chording,
+    Setting::Counter,
+    Setting::Probabilities,
+    Setting::Hints,
+    Setting::Practice,
+];
+
+/// Screen for editing the [Settings].
+#[derive(Debug, Default)]
+pub struct OptionsState {
+    pub selected: usize,
+}
+
+impl OptionsState {
+    pub fn setting(&self) -> Setting {
+        SETTINGS[self.selected]
+    }
+}
+
+impl Setting {
+    /// Change the setting to its next or previous value.
+    fn adjust(self, settings: &mut Settings, forward: bool) {
+        let assistance = &mut settings.assistance;
+
+        match self {
+            Setting::Difficulty => settings.cycle_difficulty(forward),
+            Setting::Theme => settings.cycle_theme(forward),
+            Setting::TileSize => settings.adjust_tile_size(forward),
+            Setting::Marks => settings.cycle_marks(forward),
+            Setting::Chording => settings.cycle_chording(forward),
+            Setting::Counter => settings.toggle_counter(),
+            Setting::Probabilities => assistance.probabilities = !assistance.probabilities,
+            Setting::Hints => assistance.hints = !assistance.hints,
+            Setting::Practice => assistance.practice = !assistance.practice,
+        }
+    }
+}
+
+pub fn update(app: &mut App, state: &mut State) {
+    let Stage::Options(options) = &mut state.stage else {
+        return;
+    };
+
+    let keyboard = &app.keyboard;
+
+    if keyboard.was_pressed(KeyCode::Up) {
+        options.selected = options
+            .selected
+            .checked_sub(1)
+            .unwrap_or(SETTINGS.len() - 1);
+    } else if keyboard.was_pressed(KeyCode::Down) {
+        options.selected = (options.selected + 1) % SETTINGS.len();
+    } else if keyboard.was_pressed(KeyCode::Left) {
+        options.setting().adjust(&mut state.settings, false);
+    } else if keyboard.was_pressed(KeyCode::Right) || keyboard.was_pressed(KeyCode::Return) {
+        options.setting().adjust(&mut state.settings, true);
+    } else if keyboard.was_pressed(KeyCode::Escape) {
+        state.save_settings();
+        state.open_menu(app);
+    }
+
+    if state.overlay && !state.settings.assistance.probabilities {
+        state.hide_overlay();
+    }
+}
@@ -1,11 +1,16 @@
 use notan::prelude::*;
 
+use crate::bindings::Command;
 use crate::state::State;
 
-use super::Stage;
-
 pub fn update(app: &mut App, state: &mut State) {
-    if app.keyboard.was_pressed(KeyCode::Return) {
-        state.stage = Stage::Playing;
+    if app.keyboard.ctrl() && app.keyboard.was_pressed(KeyCode::S) {
+        state.save_game();
+    }
+
+    if state.bindings.triggered(Command::Pause, app) {
+        state.resume_game();
+    } else if app.keyboard.was_pressed(KeyCode::Escape) {
+        state.open_menu(app);
     }
 }
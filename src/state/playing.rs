@@ -1,39 +1,101 @@
 use itertools::Itertools;
 use notan::prelude::*;
 
+use enimdnal_core::minefield::Cover;
+use enimdnal_core::replay::Action;
+
+use crate::bindings::{Command, Device};
+use crate::settings::Chording;
 use crate::state::defeat::{DefeatState, Explosion};
 use crate::state::{Stage, State};
 
 pub fn update(app: &mut App, state: &mut State) {
-    if state.board().is_initialized() {
-        let delta = app.timer.delta().subsec_millis();
-        state.run_timer_milisec += delta;
-    }
-
     let (mouse_x, mouse_y) = app.mouse.position();
     let board_coords = state.mouse_to_board_coords(mouse_x, mouse_y);
 
     state.hover = board_coords;
 
-    if let Some((x, y)) = board_coords {
-        if app.mouse.left_was_pressed() {
-            state.board.handle_primary_action(x, y);
-        } else if app.mouse.right_was_pressed() {
-            state.board.handle_secondary_action(x, y);
-        }
+    if !state.poll_generation() {
+        return;
+    }
+
+    if let Some(direction) = cursor_direction(&app.keyboard) {
+        state.move_cursor(direction, app.keyboard.shift(), app.keyboard.ctrl());
+    }
+
+    for command in [Command::Uncover, Command::Flag, Command::Chord] {
+        let target = match state.bindings.triggered_by(command, app) {
+            Some(Device::Mouse) => board_coords,
+            Some(Device::Keyboard) => state.cursor,
+            None => None,
+        };
+
+        let Some((x, y)) = target else {
+            continue;
+        };
+
+        let tile = state.board.tile(x, y);
+        let on_hint = tile.cover() == Cover::Down && tile.is_hint();
+
+        let action = match (command, state.settings.chording) {
+            (Command::Flag, _) => Action::Secondary(x, y),
+            (Command::Chord, Chording::Off) => continue,
+            (Command::Chord, _) => Action::Chord(x, y),
+            (_, Chording::Dedicated | Chording::Off) if on_hint => continue,
+            _ => Action::Primary(x, y),
+        };
+        state.act(action);
+    }
+
+    if app.keyboard.was_pressed(KeyCode::P) {
+        state.toggle_overlay();
+    }
+
+    if app.keyboard.was_pressed(KeyCode::F2) {
+        state.toggle_practice();
+    }
+
+    if state.bindings.triggered(Command::Undo, app) {
+        state.undo();
+    } else if state.bindings.triggered(Command::Redo, app) {
+        state.redo();
+    }
+
+    let hint_clicked = app.mouse.left_was_pressed() && state.is_over_hint_button(mouse_x, mouse_y);
+    if hint_clicked || state.bindings.triggered(Command::Hint, app) {
+        state.request_hint();
     }
 
     if state.board.is_defeat() {
-        let triggered_pos = board_coords
-            .expect("Failed to obtain board coords when transitioning playing -> defeat");
+        state.finish_run();
+        let triggered_pos = uncovered_mine(state)
+            .expect("Failed to find the triggered mine when transitioning playing -> defeat");
         transition_defeat(state, triggered_pos);
     } else if state.board.is_victory() {
+        state.finish_run();
+        state.record_score();
         state.stage = Stage::Victory;
     }
 
-    if app.keyboard.was_pressed(KeyCode::Return) {
-        state.stage = Stage::Paused;
+    if app.keyboard.ctrl() && app.keyboard.was_pressed(KeyCode::S) {
+        state.save_game();
     }
+
+    if state.bindings.triggered(Command::Pause, app) {
+        state.pause_game();
+    }
+}
+
+/// The mine that caused the defeat, whether by a click or by redoing one.
+fn uncovered_mine(state: &State) -> Option<(usize, usize)> {
+    let (width, height) = state.board().dims();
+
+    (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .find(|&(x, y)| {
+            let tile = state.board().tile(x, y);
+            tile.is_mine() && tile.cover() == Cover::Down
+        })
 }
 
 fn transition_defeat(state: &mut State, triggered_pos: (usize, usize)) {
@@ -58,11 +120,12 @@ fn transition_defeat(state: &mut State, triggered_pos: (usize, usize)) {
         }
     }
 
-    explosions.sort_by_key(|expl| distance(triggered_pos, expl.pos));
+    let board = state.board();
+    explosions.sort_by_key(|expl| board.distance(triggered_pos, expl.pos));
 
     let rings = explosions
         .iter_mut()
-        .group_by(|expl| distance(triggered_pos, expl.pos));
+        .group_by(|expl| board.distance(triggered_pos, expl.pos));
     let mut current_delay = 0;
     for (_, ring) in &rings {
         for expl in ring {
@@ -72,15 +135,25 @@ fn transition_defeat(state: &mut State, triggered_pos: (usize, usize)) {
         current_delay += EXPLOSION_RING_DELAY;
     }
 
-    state.stage = Stage::Defeat(DefeatState {
-        explosions,
-        elapsed_milisec: 0,
-    });
+    state.stage = Stage::Defeat(DefeatState::new(explosions));
 }
 
-fn distance((from_x, from_y): (usize, usize), (p_x, p_y): (usize, usize)) -> usize {
-    let x_projection = usize::abs_diff(from_x, p_x);
-    let y_projection = usize::abs_diff(from_y, p_y);
-
-    std::cmp::max(x_projection, y_projection)
+/// Direction of the cursor movement key pressed this frame.
+///
+/// Arrows, WASD and HJKL all work, except for Ctrl+S which saves the game.
+fn cursor_direction(keyboard: &Keyboard) -> Option<(isize, isize)> {
+    let pressed = |keys: &[KeyCode]| keys.iter().any(|&key| keyboard.was_pressed(key));
+    let save = keyboard.ctrl() && keyboard.was_pressed(KeyCode::S);
+
+    if pressed(&[KeyCode::Up, KeyCode::W, KeyCode::K]) {
+        Some((0, -1))
+    } else if pressed(&[KeyCode::Down, KeyCode::J]) || (pressed(&[KeyCode::S]) && !save) {
+        Some((0, 1))
+    } else if pressed(&[KeyCode::Left, KeyCode::A, KeyCode::H]) {
+        Some((-1, 0))
+    } else if pressed(&[KeyCode::Right, KeyCode::D, KeyCode::L]) {
+        Some((1, 0))
+    } else {
+        None
+    }
 }
@@ -0,0 +1,145 @@
+use std::time::Duration;
+
+use notan::prelude::*;
+
+use enimdnal_core::minefield::Board;
+use enimdnal_core::replay::{self, Replay};
+
+use crate::state::{Stage, State};
+
+/// Playback speed multipliers, switched between with the arrow keys.
+const SPEEDS: [f32; 5] = [0.5, 1., 2., 4., 8.];
+const NORMAL_SPEED: usize = 1;
+
+/// How far a single arrow key press seeks, in miliseconds.
+const SEEK_STEP: f32 = 1000.;
+
+#[derive(Debug)]
+pub struct ReplayState {
+    pub replay: Replay,
+    pub position_milisec: f32,
+    pub paused: bool,
+    speed: usize,
+
+    /// Number of recorded events already applied to the board.
+    applied: usize,
+
+    /// Board as of the first click, with the number of events applied by then,
+    /// kept so that seeking backwards does not place the mines all over again.
+    placed: Option<(usize, Box<Board>)>,
+}
+
+impl ReplayState {
+    pub fn new(replay: Replay) -> Self {
+        Self {
+            replay,
+            position_milisec: 0.,
+            paused: false,
+            speed: NORMAL_SPEED,
+            applied: 0,
+            placed: None,
+        }
+    }
+
+    pub fn speed(&self) -> f32 {
+        SPEEDS[self.speed]
+    }
+
+    pub fn progress(&self) -> f32 {
+        let duration = self.replay.duration_milisec().max(1) as f32;
+        self.position_milisec / duration
+    }
+
+    /// Bring the `board` to its state at the given point of the game.
+    ///
+    /// Seeking backwards replays everything since the first click,
+    /// which is cheap enough for boards of any sensible size.
+    pub fn seek(&mut self, board: &mut Board, position_milisec: f32) {
+        let duration = self.replay.duration_milisec() as f32;
+        let position_milisec = position_milisec.clamp(0., duration);
+
+        if position_milisec < self.position_milisec || self.applied == 0 {
+            let events = self.replay.events();
+            let placed = self
+                .placed
+                .as_ref()
+                .filter(|(applied, _)| events[applied - 1].time_milisec as f32 <= position_milisec);
+
+            (*board, self.applied) = match placed {
+                Some((applied, placed)) => (Board::clone(placed), *applied),
+                None => (self.replay.board(), 0),
+            };
+        }
+
+        self.position_milisec = position_milisec;
+
+        let pending = &self.replay.events()[self.applied..];
+        for event in pending {
+            if event.time_milisec as f32 > position_milisec {
+                break;
+            }
+
+            replay::apply(board, event.action);
+            self.applied += 1;
+
+            if self.placed.is_none() && board.is_initialized() {
+                self.placed = Some((self.applied, Box::new(board.clone())));
+            }
+        }
+    }
+
+    fn update(&mut self, app: &App, board: &mut Board) {
+        let keyboard = &app.keyboard;
+        let mut position = self.position_milisec;
+
+        if keyboard.was_pressed(KeyCode::Space) {
+            self.paused = !self.paused;
+        }
+
+        if keyboard.was_pressed(KeyCode::Up) {
+            self.speed = usize::min(self.speed + 1, SPEEDS.len() - 1);
+        } else if keyboard.was_pressed(KeyCode::Down) {
+            self.speed = self.speed.saturating_sub(1);
+        }
+
+        if keyboard.was_pressed(KeyCode::Left) {
+            position -= SEEK_STEP;
+        } else if keyboard.was_pressed(KeyCode::Right) {
+            position += SEEK_STEP;
+        } else if !self.paused {
+            position += app.timer.delta_f32() * 1000. * self.speed();
+        }
+
+        self.seek(board, position);
+    }
+}
+
+pub fn update(app: &mut App, state: &mut State) {
+    state.hover = None;
+
+    let (mouse_x, mouse_y) = app.mouse.position();
+    let scrub_target = if app.mouse.left_is_down() {
+        state.replay_bar_fraction(mouse_x, mouse_y)
+    } else {
+        None
+    };
+
+    let Stage::Replay(viewer) = &mut state.stage else {
+        return;
+    };
+
+    if let Some(fraction) = scrub_target {
+        let target = fraction * viewer.replay.duration_milisec() as f32;
+        viewer.seek(&mut state.board, target);
+    } else {
+        viewer.update(app, &mut state.board);
+    }
+
+    state
+        .clock
+        .set_elapsed(Duration::from_millis(viewer.position_milisec as u64));
+
+    if app.keyboard.was_pressed(KeyCode::Escape) {
+        state.open_menu(app);
+    }
+}
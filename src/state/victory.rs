@@ -1,13 +1,23 @@
 use notan::prelude::*;
 
-use crate::state::{Stage, State};
+use enimdnal_core::random::Seed;
+
+use crate::bindings::Command;
+use crate::state::State;
 
 pub fn update(app: &mut App, state: &mut State) {
     state.hover = None;
 
-    if app.keyboard.was_pressed(KeyCode::Space) {
-        state.stage = Stage::Playing;
-        state.run_timer_milisec = 0;
-        state.board.reset();
+    if state.bindings.triggered(Command::Restart, app) {
+        state.new_game(Seed::random());
+    } else if app.keyboard.was_pressed(KeyCode::Back) {
+        let seed = state.board.seed();
+        state.new_game(seed);
+    } else if app.keyboard.was_pressed(KeyCode::R) {
+        if let Some(replay) = state.last_replay.clone() {
+            state.watch(replay);
+        }
+    } else if app.keyboard.was_pressed(KeyCode::Escape) {
+        state.open_menu(app);
     }
 }